@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE player_task_scores;
+
+DROP TYPE POOL_KIND;
@@ -0,0 +1,18 @@
+-- Your SQL goes here
+CREATE TYPE POOL_KIND AS ENUM ('fixed', 'relative_to_completion');
+
+CREATE TABLE player_task_scores (
+    id SERIAL PRIMARY KEY,
+    player_id VARCHAR NOT NULL,
+    task TASK_TYPE,
+    tx_kind TX_KIND,
+    pool_kind POOL_KIND NOT NULL,
+    pool_total DOUBLE PRECISION NOT NULL,
+    completed_by BIGINT NOT NULL,
+    share BIGINT NOT NULL,
+    CONSTRAINT fk_player FOREIGN KEY(player_id) REFERENCES players(id) ON DELETE CASCADE,
+    -- each row scores either an identified or an unidentified task
+    CONSTRAINT task_xor_tx_kind CHECK ((task IS NULL) <> (tx_kind IS NULL))
+);
+
+CREATE INDEX player_task_scores_player_id ON player_task_scores (player_id);
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+
+-- NB: postgres can't drop values from an enum type, and
+-- the values left behind are unused after the next migration
+-- is reverted
+SELECT 1;
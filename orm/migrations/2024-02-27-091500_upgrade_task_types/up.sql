@@ -0,0 +1,4 @@
+-- Your SQL goes here
+ALTER TYPE TASK_TYPE ADD VALUE IF NOT EXISTS 'delegate_stake_before_upgrade';
+ALTER TYPE TASK_TYPE ADD VALUE IF NOT EXISTS 'vote_upgrade';
+ALTER TYPE TASK_TYPE ADD VALUE IF NOT EXISTS 'sign_first_block_of_upgrade';
@@ -0,0 +1,45 @@
+-- This file should undo anything in `up.sql`
+
+-- tasks of upgrades unknown to the legacy campaign can't be
+-- represented by the per upgrade task types
+DELETE FROM tasks
+WHERE task IN ('delegate_stake_before_upgrade', 'vote_upgrade', 'sign_first_block_of_upgrade')
+  AND upgrade NOT IN ('v0_to_v1', 'v1_to_v2');
+
+DELETE FROM tasks
+WHERE task = 'sign_first_block_of_upgrade'
+  AND upgrade = 'v0_to_v1';
+
+UPDATE tasks
+SET task = 'delegate_stake_on_v0'
+WHERE task = 'delegate_stake_before_upgrade' AND upgrade = 'v0_to_v1';
+
+UPDATE tasks
+SET task = 'delegate_stake_on_v1'
+WHERE task = 'delegate_stake_before_upgrade' AND upgrade = 'v1_to_v2';
+
+UPDATE tasks
+SET task = 'vote_upgrade_v0_to_v1'
+WHERE task = 'vote_upgrade' AND upgrade = 'v0_to_v1';
+
+UPDATE tasks
+SET task = 'vote_upgrade_v1_to_v2'
+WHERE task = 'vote_upgrade' AND upgrade = 'v1_to_v2';
+
+UPDATE tasks
+SET task = 'sign_first_block_of_upgrade_to_v2'
+WHERE task = 'sign_first_block_of_upgrade' AND upgrade = 'v1_to_v2';
+
+DELETE FROM player_task_scores;
+
+ALTER TABLE tasks
+DROP CONSTRAINT tasks_player_id_task_upgrade_key;
+
+ALTER TABLE tasks
+ADD UNIQUE (player_id, task);
+
+ALTER TABLE player_task_scores
+DROP COLUMN upgrade;
+
+ALTER TABLE tasks
+DROP COLUMN upgrade;
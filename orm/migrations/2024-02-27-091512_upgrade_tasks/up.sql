@@ -0,0 +1,41 @@
+-- Your SQL goes here
+
+-- name of the network upgrade a task was completed for,
+-- or an empty string for tasks not bound to an upgrade
+ALTER TABLE tasks
+ADD COLUMN upgrade VARCHAR NOT NULL DEFAULT '';
+
+ALTER TABLE player_task_scores
+ADD COLUMN upgrade VARCHAR NOT NULL DEFAULT '';
+
+-- upgrade bound tasks may be completed once per upgrade
+ALTER TABLE tasks
+DROP CONSTRAINT tasks_player_id_task_key;
+
+ALTER TABLE tasks
+ADD UNIQUE (player_id, task, upgrade);
+
+-- migrate the per upgrade tasks to their generic counterparts,
+-- using the upgrade names of the legacy campaign config
+UPDATE tasks
+SET task = 'delegate_stake_before_upgrade', upgrade = 'v0_to_v1'
+WHERE task = 'delegate_stake_on_v0';
+
+UPDATE tasks
+SET task = 'delegate_stake_before_upgrade', upgrade = 'v1_to_v2'
+WHERE task = 'delegate_stake_on_v1';
+
+UPDATE tasks
+SET task = 'vote_upgrade', upgrade = 'v0_to_v1'
+WHERE task = 'vote_upgrade_v0_to_v1';
+
+UPDATE tasks
+SET task = 'vote_upgrade', upgrade = 'v1_to_v2'
+WHERE task = 'vote_upgrade_v1_to_v2';
+
+UPDATE tasks
+SET task = 'sign_first_block_of_upgrade', upgrade = 'v1_to_v2'
+WHERE task = 'sign_first_block_of_upgrade_to_v2';
+
+-- task scores are recomputed from scratch by the extractor
+DELETE FROM player_task_scores;
@@ -0,0 +1,10 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE unidentified_tasks
+DROP COLUMN completed_by_tx,
+DROP COLUMN completed_at,
+DROP COLUMN completed_at_height;
+
+ALTER TABLE tasks
+DROP COLUMN completed_by_tx,
+DROP COLUMN completed_at,
+DROP COLUMN completed_at_height;
@@ -0,0 +1,15 @@
+-- Your SQL goes here
+
+-- block and transaction that completed a task. these are null
+-- for manual tasks, and for tasks completed before they were
+-- recorded. pilot tasks not completed by a transaction store
+-- the block that evidences their completion
+ALTER TABLE tasks
+ADD COLUMN completed_at_height INT,
+ADD COLUMN completed_at TIMESTAMP,
+ADD COLUMN completed_by_tx VARCHAR(64);
+
+ALTER TABLE unidentified_tasks
+ADD COLUMN completed_at_height INT,
+ADD COLUMN completed_at TIMESTAMP,
+ADD COLUMN completed_by_tx VARCHAR(64);
@@ -0,0 +1,20 @@
+-- Your SQL goes here
+
+-- scores and rankings of all players, taken once per epoch
+CREATE TABLE score_snapshots (
+    id SERIAL PRIMARY KEY,
+    epoch INT NOT NULL,
+    -- last block whose tasks had been processed when the
+    -- snapshot was taken
+    height INT NOT NULL,
+    taken_at TIMESTAMP NOT NULL,
+    player_id VARCHAR NOT NULL,
+    score BIGINT NOT NULL,
+    ranking INT,
+    CONSTRAINT fk_player FOREIGN KEY(player_id) REFERENCES players(id) ON DELETE CASCADE
+);
+
+ALTER TABLE score_snapshots
+ADD UNIQUE (epoch, player_id);
+
+CREATE INDEX score_snapshots_player_id ON score_snapshots (player_id);
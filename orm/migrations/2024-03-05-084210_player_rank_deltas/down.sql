@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+
+ALTER TABLE player_ranks
+DROP COLUMN ranking_delta,
+DROP COLUMN previous_ranking;
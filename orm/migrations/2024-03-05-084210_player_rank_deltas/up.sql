@@ -0,0 +1,6 @@
+-- Your SQL goes here
+
+ALTER TABLE player_ranks
+ADD COLUMN previous_ranking INT,
+-- positive when a player climbed up the leaderboard
+ADD COLUMN ranking_delta INT;
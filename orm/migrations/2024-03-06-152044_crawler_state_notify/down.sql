@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+
+DROP TRIGGER crawler_state_advanced ON crawler_state;
+
+DROP FUNCTION notify_crawler_state_advanced;
@@ -0,0 +1,16 @@
+-- Your SQL goes here
+
+-- notify listeners on the `crawler_state_advanced` channel with
+-- the new height, whenever the crawler indexes new blocks
+CREATE FUNCTION notify_crawler_state_advanced() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'INSERT' OR NEW.height IS DISTINCT FROM OLD.height THEN
+        PERFORM pg_notify('crawler_state_advanced', NEW.height::TEXT);
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER crawler_state_advanced
+AFTER INSERT OR UPDATE ON crawler_state
+FOR EACH ROW EXECUTE FUNCTION notify_crawler_state_advanced();
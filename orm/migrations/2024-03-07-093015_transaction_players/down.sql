@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+
+DROP INDEX transactions_player_id;
+
+ALTER TABLE transactions
+DROP COLUMN player_id;
@@ -0,0 +1,10 @@
+-- Your SQL goes here
+
+-- player the memo of a transaction resolves to, recorded when the
+-- score extractor processes the transaction. it is null for memos
+-- that resolve to no player, and for transactions processed before
+-- it was recorded, until they are reprocessed
+ALTER TABLE transactions
+ADD COLUMN player_id VARCHAR;
+
+CREATE INDEX transactions_player_id ON transactions (player_id);
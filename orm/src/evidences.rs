@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::schema::evidences;
 
-#[derive(Debug, Clone, Serialize, Deserialize, diesel_derive_enum::DbEnum)]
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, diesel_derive_enum::DbEnum)]
 #[ExistingTypePath = "crate::schema::sql_types::EvidenceKind"]
 pub enum EvidenceKindDb {
     DuplicateVote,
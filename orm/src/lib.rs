@@ -1,3 +1,25 @@
+/// Define a fieldless enum along with an `ALL` constant listing each
+/// of its variants, in declaration order, such that new variants can
+/// not be left out of it.
+macro_rules! enum_with_all {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {
+            $($variant:ident),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis enum $name {
+            $($variant),*
+        }
+
+        impl $name {
+            /// All the variants of this enum, in declaration order.
+            pub const ALL: [Self; [$(stringify!($variant)),*].len()] = [$(Self::$variant),*];
+        }
+    };
+}
+
 pub mod block;
 pub mod chain_params;
 pub mod commits;
@@ -6,8 +28,10 @@ pub mod evidences;
 pub mod governance_proposals;
 pub mod governance_votes;
 pub mod player_ranks;
+pub mod player_task_scores;
 pub mod players;
 pub mod schema;
+pub mod score_snapshots;
 pub mod stewards;
 pub mod task_completion_state;
 pub mod tasks;
@@ -10,6 +10,8 @@ pub struct PlayerRankDb {
     pub id: i32,
     pub ranking: i32,
     pub player_id: String,
+    pub previous_ranking: Option<i32>,
+    pub ranking_delta: Option<i32>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Insertable)]
@@ -18,4 +20,6 @@ pub struct PlayerRankDb {
 pub struct PlayerRankInsertDb {
     pub ranking: i32,
     pub player_id: String,
+    pub previous_ranking: Option<i32>,
+    pub ranking_delta: Option<i32>,
 }
@@ -0,0 +1,42 @@
+use diesel::{Insertable, Queryable, Selectable};
+use serde::{Deserialize, Serialize};
+
+use crate::schema::player_task_scores;
+use crate::tasks::TaskTypeDb;
+use crate::transaction::TransactionKindDb;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, diesel_derive_enum::DbEnum)]
+#[ExistingTypePath = "crate::schema::sql_types::PoolKind"]
+pub enum PoolKindDb {
+    Fixed,
+    RelativeToCompletion,
+}
+
+#[derive(Debug, Serialize, Queryable, Selectable, Clone)]
+#[diesel(table_name = player_task_scores)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct PlayerTaskScoreDb {
+    pub id: i32,
+    pub player_id: String,
+    pub task: Option<TaskTypeDb>,
+    pub tx_kind: Option<TransactionKindDb>,
+    pub pool_kind: PoolKindDb,
+    pub pool_total: f64,
+    pub completed_by: i64,
+    pub share: i64,
+    pub upgrade: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = player_task_scores)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct PlayerTaskScoreInsertDb {
+    pub player_id: String,
+    pub task: Option<TaskTypeDb>,
+    pub tx_kind: Option<TransactionKindDb>,
+    pub pool_kind: PoolKindDb,
+    pub pool_total: f64,
+    pub completed_by: i64,
+    pub share: i64,
+    pub upgrade: String,
+}
@@ -17,6 +17,10 @@ pub mod sql_types {
     #[diesel(postgres_type(name = "player_kind"))]
     pub struct PlayerKind;
 
+    #[derive(diesel::query_builder::QueryId, std::fmt::Debug, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "pool_kind"))]
+    pub struct PoolKind;
+
     #[derive(diesel::query_builder::QueryId, std::fmt::Debug, diesel::sql_types::SqlType)]
     #[diesel(postgres_type(name = "task_type"))]
     pub struct TaskType;
@@ -141,6 +145,27 @@ diesel::table! {
         id -> Int4,
         ranking -> Int4,
         player_id -> Varchar,
+        previous_ranking -> Nullable<Int4>,
+        ranking_delta -> Nullable<Int4>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::TaskType;
+    use super::sql_types::TxKind;
+    use super::sql_types::PoolKind;
+
+    player_task_scores (id) {
+        id -> Int4,
+        player_id -> Varchar,
+        task -> Nullable<TaskType>,
+        tx_kind -> Nullable<TxKind>,
+        pool_kind -> PoolKind,
+        pool_total -> Float8,
+        completed_by -> Int8,
+        share -> Int8,
+        upgrade -> Varchar,
     }
 }
 
@@ -163,6 +188,18 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    score_snapshots (id) {
+        id -> Int4,
+        epoch -> Int4,
+        height -> Int4,
+        taken_at -> Timestamp,
+        player_id -> Varchar,
+        score -> Int8,
+        ranking -> Nullable<Int4>,
+    }
+}
+
 diesel::table! {
     stewards (id) {
         id -> Int4,
@@ -187,6 +224,11 @@ diesel::table! {
         id -> Int4,
         task -> TaskType,
         player_id -> Varchar,
+        upgrade -> Varchar,
+        completed_at_height -> Nullable<Int4>,
+        completed_at -> Nullable<Timestamp>,
+        #[max_length = 64]
+        completed_by_tx -> Nullable<Varchar>,
     }
 }
 
@@ -217,6 +259,7 @@ diesel::table! {
         memo -> Nullable<Bytea>,
         #[max_length = 64]
         block_id -> Varchar,
+        player_id -> Nullable<Varchar>,
     }
 }
 
@@ -228,6 +271,10 @@ diesel::table! {
         id -> Int4,
         tx_kind -> TxKind,
         player_id -> Varchar,
+        completed_at_height -> Nullable<Int4>,
+        completed_at -> Nullable<Timestamp>,
+        #[max_length = 64]
+        completed_by_tx -> Nullable<Varchar>,
     }
 }
 
@@ -254,6 +301,8 @@ diesel::joinable!(governance_votes -> governance_proposals (proposal_id));
 diesel::joinable!(governance_votes -> transactions (transaction_id));
 diesel::joinable!(manual_tasks -> players (player_id));
 diesel::joinable!(player_ranks -> players (player_id));
+diesel::joinable!(player_task_scores -> players (player_id));
+diesel::joinable!(score_snapshots -> players (player_id));
 diesel::joinable!(tasks -> players (player_id));
 diesel::joinable!(transactions -> blocks (block_id));
 diesel::joinable!(unidentified_tasks -> players (player_id));
@@ -268,7 +317,9 @@ diesel::allow_tables_to_appear_in_same_query!(
     governance_votes,
     manual_tasks,
     player_ranks,
+    player_task_scores,
     players,
+    score_snapshots,
     stewards,
     task_completion_state,
     tasks,
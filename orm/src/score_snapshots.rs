@@ -0,0 +1,18 @@
+use chrono::NaiveDateTime;
+use diesel::{Queryable, Selectable};
+use serde::Serialize;
+
+use crate::schema::score_snapshots;
+
+#[derive(Debug, Serialize, Queryable, Selectable, Clone)]
+#[diesel(table_name = score_snapshots)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ScoreSnapshotDb {
+    pub id: i32,
+    pub epoch: i32,
+    pub height: i32,
+    pub taken_at: NaiveDateTime,
+    pub player_id: String,
+    pub score: i64,
+    pub ranking: Option<i32>,
+}
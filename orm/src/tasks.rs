@@ -1,67 +1,96 @@
+use chrono::NaiveDateTime;
 use diesel::{Insertable, Queryable, Selectable};
 use serde::{Deserialize, Serialize};
 
 use super::transaction::TransactionKindDb;
 use crate::schema::{tasks, unidentified_tasks};
 
-#[derive(
-    Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize, diesel_derive_enum::DbEnum,
-)]
-#[ExistingTypePath = "crate::schema::sql_types::TaskType"]
-pub enum TaskTypeDb {
-    // crew tasks (tx)
-    DelegateStakeOnV0,
-    DelegateStakeOnV1,
-    ClaimPosRewards,
-    ShieldNaan,
-    UnshieldNaan,
-    ShieldToShielded,
-    ShieldAssetOverIbc,
-    // pilot tasks (genesis)
-    SubmitPreGenesisBondTx,
-    // pilot tasks (tx)
-    VotePgfStewardProposal,
-    VoteUpgradeV0ToV1,
-    VoteUpgradeV1ToV2,
-    InitPostGenesisValidator,
-    // pilot tasks (completable, non-tx)
-    StartNode5MinFromGenesis,
-    InValidatorSetFor1Epoch,
-    SignFirstBlockOfUpgradeToV2,
-    // pilot tasks (ongoing, non-tx)
-    Keep99PerCentUptime,
-    Keep95PerCentUptime,
-    Keep99PerCentGovParticipationRate,
-    Keep90PerCentGovParticipationRate,
-    // manual tasks (either pilot or crew)
-    ProvidePublicRpcEndpoint,
-    OperateNamadaIndexer,
-    OperateNamadaInterface,
-    OperateCosmosTestnetRelayer,
-    OperateOsmosisTestnetRelayer,
-    OperateNobleTestnetRelayer,
-    OperateRelayerOnNetWithNfts,
-    OperateRelayerOnAnotherNet,
-    IntegrateSeInBlockExplorer,
-    IntegrateSeInBrowserWallet,
-    IntegrateSeInAndroidWallet,
-    IntegrateSeInIosWallet,
-    IntegrateSeInAnotherWallet,
-    SupportShieldedTxsInBlockExplorer,
-    SupportShieldedTxsInBrowserWallet,
-    SupportShieldedTxsInAndroidWallet,
-    SupportShieldedTxsInIosWallet,
-    BuildAdditionalFossTooling,
-    BuildWebAppWithShieldedActionOnIbcChain,
-    OsmosisFrontendShieldedSwaps,
-    AnotherAppWithShieldedActionOnIbcChain,
-    ReduceMaspProofGenTime,
-    IncreaseNoteScanSpeed,
-    FindAndProveNamSpecsFlaw,
-    OptimizeNamSmExecSpeed,
-    FindProtocolSecVulnerability,
+enum_with_all! {
+    #[derive(
+        Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize, diesel_derive_enum::DbEnum,
+    )]
+    #[ExistingTypePath = "crate::schema::sql_types::TaskType"]
+    pub enum TaskTypeDb {
+        // crew tasks (tx)
+        // NB: superseded by `DelegateStakeBeforeUpgrade`
+        DelegateStakeOnV0,
+        // NB: superseded by `DelegateStakeBeforeUpgrade`
+        DelegateStakeOnV1,
+        ClaimPosRewards,
+        ShieldNaan,
+        UnshieldNaan,
+        ShieldToShielded,
+        ShieldAssetOverIbc,
+        // pilot tasks (genesis)
+        SubmitPreGenesisBondTx,
+        // pilot tasks (tx)
+        VotePgfStewardProposal,
+        // NB: superseded by `VoteUpgrade`
+        VoteUpgradeV0ToV1,
+        // NB: superseded by `VoteUpgrade`
+        VoteUpgradeV1ToV2,
+        InitPostGenesisValidator,
+        // pilot tasks (completable, non-tx)
+        StartNode5MinFromGenesis,
+        InValidatorSetFor1Epoch,
+        // NB: superseded by `SignFirstBlockOfUpgrade`
+        SignFirstBlockOfUpgradeToV2,
+        // pilot tasks (ongoing, non-tx)
+        Keep99PerCentUptime,
+        Keep95PerCentUptime,
+        Keep99PerCentGovParticipationRate,
+        Keep90PerCentGovParticipationRate,
+        // manual tasks (either pilot or crew)
+        ProvidePublicRpcEndpoint,
+        OperateNamadaIndexer,
+        OperateNamadaInterface,
+        OperateCosmosTestnetRelayer,
+        OperateOsmosisTestnetRelayer,
+        OperateNobleTestnetRelayer,
+        OperateRelayerOnNetWithNfts,
+        OperateRelayerOnAnotherNet,
+        IntegrateSeInBlockExplorer,
+        IntegrateSeInBrowserWallet,
+        IntegrateSeInAndroidWallet,
+        IntegrateSeInIosWallet,
+        IntegrateSeInAnotherWallet,
+        SupportShieldedTxsInBlockExplorer,
+        SupportShieldedTxsInBrowserWallet,
+        SupportShieldedTxsInAndroidWallet,
+        SupportShieldedTxsInIosWallet,
+        BuildAdditionalFossTooling,
+        BuildWebAppWithShieldedActionOnIbcChain,
+        OsmosisFrontendShieldedSwaps,
+        AnotherAppWithShieldedActionOnIbcChain,
+        ReduceMaspProofGenTime,
+        IncreaseNoteScanSpeed,
+        FindAndProveNamSpecsFlaw,
+        OptimizeNamSmExecSpeed,
+        FindProtocolSecVulnerability,
+        // upgrade bound tasks, completed once per network upgrade
+        DelegateStakeBeforeUpgrade,
+        VoteUpgrade,
+        SignFirstBlockOfUpgrade,
+    }
 }
 
+impl TaskTypeDb {
+    /// Task types completed once per network upgrade.
+    pub const UPGRADE_BOUND: [Self; 3] = [
+        Self::DelegateStakeBeforeUpgrade,
+        Self::VoteUpgrade,
+        Self::SignFirstBlockOfUpgrade,
+    ];
+
+    /// Check if this task type is completed once per network upgrade.
+    pub fn is_upgrade_bound(&self) -> bool {
+        Self::UPGRADE_BOUND.contains(self)
+    }
+}
+
+/// Value of the `upgrade` column of tasks not bound to a network upgrade.
+pub const NO_UPGRADE: &str = "";
+
 #[derive(Debug, Serialize, Queryable, Selectable, Clone)]
 #[diesel(table_name = tasks)]
 #[diesel(check_for_backend(diesel::pg::Pg))]
@@ -69,6 +98,10 @@ pub struct TaskDb {
     pub id: i32,
     pub task: TaskTypeDb,
     pub player_id: String,
+    pub upgrade: String,
+    pub completed_at_height: Option<i32>,
+    pub completed_at: Option<NaiveDateTime>,
+    pub completed_by_tx: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Insertable)]
@@ -77,6 +110,10 @@ pub struct TaskDb {
 pub struct TaskInsertDb {
     pub task: TaskTypeDb,
     pub player_id: String,
+    pub upgrade: String,
+    pub completed_at_height: Option<i32>,
+    pub completed_at: Option<NaiveDateTime>,
+    pub completed_by_tx: Option<String>,
 }
 
 #[derive(Debug, Serialize, Queryable, Selectable, Clone)]
@@ -86,6 +123,9 @@ pub struct UnidentifiedTaskDb {
     pub id: i32,
     pub tx_kind: TransactionKindDb,
     pub player_id: String,
+    pub completed_at_height: Option<i32>,
+    pub completed_at: Option<NaiveDateTime>,
+    pub completed_by_tx: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Insertable)]
@@ -94,4 +134,7 @@ pub struct UnidentifiedTaskDb {
 pub struct UnidentifiedTaskInsertDb {
     pub tx_kind: TransactionKindDb,
     pub player_id: String,
+    pub completed_at_height: Option<i32>,
+    pub completed_at: Option<NaiveDateTime>,
+    pub completed_by_tx: Option<String>,
 }
@@ -3,38 +3,40 @@ use serde::{Deserialize, Serialize};
 
 use crate::schema::transactions;
 
-#[derive(
-    Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize, diesel_derive_enum::DbEnum,
-)]
-#[ExistingTypePath = "crate::schema::sql_types::TxKind"]
-pub enum TransactionKindDb {
-    Wrapper,
-    Protocol,
-    TransparentTransfer,
-    ShieldedTransfer,
-    Bond,
-    Redelegation,
-    Unbond,
-    Withdraw,
-    ClaimRewards,
-    ReactivateValidator,
-    DeactivateValidator,
-    IbcEnvelop,
-    IbcTransparentTransfer,
-    IbcShieldedTransfer,
-    ChangeConsensusKey,
-    ChangeCommission,
-    ChangeMetadata,
-    BecomeValidator,
-    InitAccount,
-    InitProposal,
-    ResignSteward,
-    RevealPublicKey,
-    UnjailValidator,
-    UpdateAccount,
-    UpdateStewardCommissions,
-    ProposalVote,
-    Unknown,
+enum_with_all! {
+    #[derive(
+        Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize, diesel_derive_enum::DbEnum,
+    )]
+    #[ExistingTypePath = "crate::schema::sql_types::TxKind"]
+    pub enum TransactionKindDb {
+        Wrapper,
+        Protocol,
+        TransparentTransfer,
+        ShieldedTransfer,
+        Bond,
+        Redelegation,
+        Unbond,
+        Withdraw,
+        ClaimRewards,
+        ReactivateValidator,
+        DeactivateValidator,
+        IbcEnvelop,
+        IbcTransparentTransfer,
+        IbcShieldedTransfer,
+        ChangeConsensusKey,
+        ChangeCommission,
+        ChangeMetadata,
+        BecomeValidator,
+        InitAccount,
+        InitProposal,
+        ResignSteward,
+        RevealPublicKey,
+        UnjailValidator,
+        UpdateAccount,
+        UpdateStewardCommissions,
+        ProposalVote,
+        Unknown,
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, diesel_derive_enum::DbEnum)]
@@ -0,0 +1,616 @@
+use std::net::SocketAddr;
+
+use anyhow::Context as AnyhowContext;
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use shared::orm::player_task_scores::PoolKindDb;
+use shared::orm::players::PlayerKindDb;
+use shared::orm::schema;
+use shared::orm::tasks::{TaskTypeDb, NO_UPGRADE};
+use shared::orm::transaction::TransactionKindDb;
+use tower_http::trace::TraceLayer;
+
+use crate::context::Context;
+use crate::db;
+use crate::last_state;
+use crate::leader::{self, LeaderLockHolder};
+use crate::players::player_exists;
+use crate::tasks::{self, TxTaskDecision};
+
+const DEFAULT_PAGE_SIZE: i64 = 50;
+const MAX_PAGE_SIZE: i64 = 100;
+/// Last page that can be requested, which keeps the offset of pages
+/// far from overflowing.
+const MAX_PAGE: i64 = 1_000_000;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ApiError {
+    #[error("{0} not found")]
+    NotFound(String),
+    #[error("Internal server error")]
+    Internal(#[from] anyhow::Error),
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::Internal(err) => {
+                tracing::error!(reason = ?err, "Failed to handle API request");
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        };
+        let body = Json(ErrorResponse {
+            message: self.to_string(),
+        });
+        (status, body).into_response()
+    }
+}
+
+#[derive(Serialize)]
+struct ErrorResponse {
+    message: String,
+}
+
+#[derive(Serialize)]
+pub struct PlayerResponse {
+    pub id: String,
+    pub moniker: String,
+    pub kind: PlayerKindDb,
+    pub namada_player_address: String,
+    pub namada_validator_address: Option<String>,
+    pub avatar_url: Option<String>,
+    pub score: i64,
+    pub ranking: Option<i32>,
+    pub previous_ranking: Option<i32>,
+    /// Number of places climbed since the previous rankings,
+    /// negative if the player dropped.
+    pub ranking_delta: Option<i32>,
+}
+
+#[derive(Serialize)]
+pub struct PlayerTaskResponse {
+    pub task: Option<TaskTypeDb>,
+    pub tx_kind: Option<TransactionKindDb>,
+    /// Network upgrade the task was completed for, if it is upgrade bound.
+    pub upgrade: Option<String>,
+    pub pool_kind: PoolKindDb,
+    pub pool_total: f64,
+    pub completed_by: i64,
+    pub share: i64,
+}
+
+#[derive(Serialize)]
+pub struct ScoreSnapshotResponse {
+    pub epoch: i32,
+    pub height: i32,
+    pub taken_at: chrono::NaiveDateTime,
+    pub score: i64,
+    pub ranking: Option<i32>,
+}
+
+#[derive(Serialize)]
+pub struct LeaderboardEntryResponse {
+    pub ranking: i32,
+    pub previous_ranking: Option<i32>,
+    /// Number of places climbed since the previous rankings,
+    /// negative if the player dropped.
+    pub ranking_delta: Option<i32>,
+    pub player_id: String,
+    pub moniker: String,
+    pub avatar_url: Option<String>,
+    pub score: i64,
+}
+
+#[derive(Serialize)]
+pub struct LeaderboardResponse {
+    pub kind: PlayerKindDb,
+    /// Epoch of the score snapshot the leaderboard was taken from,
+    /// if it does not reflect the current standings.
+    pub epoch: Option<i32>,
+    pub page: i64,
+    pub per_page: i64,
+    pub total: i64,
+    pub entries: Vec<LeaderboardEntryResponse>,
+}
+
+#[derive(Serialize)]
+pub struct StatusResponse {
+    pub last_processed_height: Option<i32>,
+    pub crawler_height: Option<i32>,
+    pub lag: Option<i32>,
+    /// Replica currently in charge of updating the database.
+    pub leader: Option<LeaderLockHolder>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LeaderboardKind {
+    Pilot,
+    Crew,
+}
+
+impl From<LeaderboardKind> for PlayerKindDb {
+    fn from(kind: LeaderboardKind) -> Self {
+        match kind {
+            LeaderboardKind::Pilot => PlayerKindDb::Pilot,
+            LeaderboardKind::Crew => PlayerKindDb::Crew,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Pagination {
+    pub page: Option<i64>,
+    pub per_page: Option<i64>,
+}
+
+impl Pagination {
+    fn page(&self) -> i64 {
+        self.page.unwrap_or(1).clamp(1, MAX_PAGE)
+    }
+
+    fn per_page(&self) -> i64 {
+        self.per_page
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AsOfEpoch {
+    pub epoch: Option<i32>,
+}
+
+/// Serve the HTTP API on the given address, until the server fails.
+pub async fn serve(addr: SocketAddr, cx: Context) -> anyhow::Result<()> {
+    let app = Router::new()
+        .route("/players/:id", get(get_player))
+        .route("/players/:id/tasks", get(get_player_tasks))
+        .route("/players/:id/history", get(get_player_history))
+        .route(
+            "/players/:id/task-decisions",
+            get(get_player_task_decisions),
+        )
+        .route(
+            "/transactions/:hash/task-decisions",
+            get(get_tx_task_decisions),
+        )
+        .route("/leaderboard/:kind", get(get_leaderboard))
+        .route("/status", get(get_status))
+        .layer(TraceLayer::new_for_http())
+        .with_state(cx);
+
+    tracing::info!(%addr, "Serving HTTP API");
+
+    axum::Server::bind(&addr)
+        .serve(app.into_make_service())
+        .await
+        .context("HTTP API server failed")
+}
+
+/// Run a read-only database operation on a pooled connection.
+async fn with_read_only_conn<O, R>(cx: &Context, op: O) -> Result<R, ApiError>
+where
+    O: FnOnce(&mut db::Connection) -> anyhow::Result<R> + Send + 'static,
+    R: Send + 'static,
+{
+    let result = cx
+        .db_connection_pool()
+        .with(|conn| conn.build_transaction().read_only().run(op))
+        .await??;
+    Ok(result)
+}
+
+async fn get_player(
+    State(cx): State<Context>,
+    Path(player_id): Path<String>,
+) -> Result<Json<PlayerResponse>, ApiError> {
+    let not_found = ApiError::NotFound(format!("Player {player_id}"));
+
+    with_read_only_conn(&cx, move |conn| {
+        use diesel::prelude::*;
+        use schema::player_ranks;
+        use schema::players;
+
+        players::table
+            .left_join(player_ranks::table)
+            .filter(players::dsl::id.eq(&player_id))
+            .select((
+                players::dsl::id,
+                players::dsl::moniker,
+                players::dsl::kind,
+                players::dsl::namada_player_address,
+                players::dsl::namada_validator_address,
+                players::dsl::avatar_url,
+                players::dsl::score,
+                player_ranks::dsl::ranking.nullable(),
+                player_ranks::dsl::previous_ranking.nullable(),
+                player_ranks::dsl::ranking_delta.nullable(),
+            ))
+            .first(conn)
+            .optional()
+            .with_context(|| format!("Failed to query player {player_id}"))
+    })
+    .await?
+    .map(
+        |(
+            id,
+            moniker,
+            kind,
+            namada_player_address,
+            namada_validator_address,
+            avatar_url,
+            score,
+            ranking,
+            previous_ranking,
+            ranking_delta,
+        )| {
+            Json(PlayerResponse {
+                id,
+                moniker,
+                kind,
+                namada_player_address,
+                namada_validator_address,
+                avatar_url,
+                score,
+                ranking,
+                previous_ranking,
+                ranking_delta,
+            })
+        },
+    )
+    .ok_or(not_found)
+}
+
+async fn get_player_tasks(
+    State(cx): State<Context>,
+    Path(player_id): Path<String>,
+) -> Result<Json<Vec<PlayerTaskResponse>>, ApiError> {
+    let not_found = ApiError::NotFound(format!("Player {player_id}"));
+
+    with_read_only_conn(&cx, move |conn| {
+        use diesel::prelude::*;
+        use schema::player_task_scores;
+        use shared::orm::player_task_scores::PlayerTaskScoreDb;
+
+        if !crate::players::player_exists(conn, &player_id)? {
+            return Ok(None);
+        }
+
+        let task_scores = player_task_scores::table
+            .filter(player_task_scores::dsl::player_id.eq(&player_id))
+            .order(player_task_scores::dsl::id)
+            .select(PlayerTaskScoreDb::as_select())
+            .load(conn)
+            .with_context(|| format!("Failed to query task scores of player {player_id}"))?;
+
+        Ok(Some(task_scores))
+    })
+    .await?
+    .map(|task_scores| {
+        Json(
+            task_scores
+                .into_iter()
+                .map(|task_score| PlayerTaskResponse {
+                    task: task_score.task,
+                    tx_kind: task_score.tx_kind,
+                    upgrade: Some(task_score.upgrade).filter(|upgrade| upgrade != NO_UPGRADE),
+                    pool_kind: task_score.pool_kind,
+                    pool_total: task_score.pool_total,
+                    completed_by: task_score.completed_by,
+                    share: task_score.share,
+                })
+                .collect(),
+        )
+    })
+    .ok_or(not_found)
+}
+
+async fn get_player_history(
+    State(cx): State<Context>,
+    Path(player_id): Path<String>,
+) -> Result<Json<Vec<ScoreSnapshotResponse>>, ApiError> {
+    let not_found = ApiError::NotFound(format!("Player {player_id}"));
+
+    with_read_only_conn(&cx, move |conn| {
+        use diesel::prelude::*;
+        use schema::score_snapshots;
+        use shared::orm::score_snapshots::ScoreSnapshotDb;
+
+        if !crate::players::player_exists(conn, &player_id)? {
+            return Ok(None);
+        }
+
+        let snapshots = score_snapshots::table
+            .filter(score_snapshots::dsl::player_id.eq(&player_id))
+            .order(score_snapshots::dsl::epoch)
+            .select(ScoreSnapshotDb::as_select())
+            .load(conn)
+            .with_context(|| format!("Failed to query score history of player {player_id}"))?;
+
+        Ok(Some(snapshots))
+    })
+    .await?
+    .map(|snapshots| {
+        Json(
+            snapshots
+                .into_iter()
+                .map(|snapshot| ScoreSnapshotResponse {
+                    epoch: snapshot.epoch,
+                    height: snapshot.height,
+                    taken_at: snapshot.taken_at,
+                    score: snapshot.score,
+                    ranking: snapshot.ranking,
+                })
+                .collect(),
+        )
+    })
+    .ok_or(not_found)
+}
+
+async fn get_leaderboard(
+    State(cx): State<Context>,
+    Path(kind): Path<LeaderboardKind>,
+    Query(pagination): Query<Pagination>,
+    Query(as_of): Query<AsOfEpoch>,
+) -> Result<Json<LeaderboardResponse>, ApiError> {
+    let player_kind: PlayerKindDb = kind.into();
+    let page = pagination.page();
+    let per_page = pagination.per_page();
+
+    if let Some(epoch) = as_of.epoch {
+        return get_leaderboard_as_of(&cx, player_kind, epoch, page, per_page).await;
+    }
+
+    let query_kind = player_kind.clone();
+    let (total, entries) = with_read_only_conn(&cx, move |conn| {
+        use diesel::dsl::count_star;
+        use diesel::prelude::*;
+        use schema::player_ranks;
+        use schema::players;
+
+        let ranked_players = player_ranks::table
+            .inner_join(players::table)
+            .filter(players::dsl::kind.eq(&query_kind));
+
+        let total: i64 = ranked_players
+            .clone()
+            .select(count_star())
+            .first(conn)
+            .with_context(|| format!("Failed to count ranked {query_kind} players"))?;
+
+        let entries = ranked_players
+            .order(player_ranks::dsl::ranking)
+            .limit(per_page)
+            .offset((page - 1) * per_page)
+            .select((
+                player_ranks::dsl::ranking,
+                player_ranks::dsl::previous_ranking,
+                player_ranks::dsl::ranking_delta,
+                players::dsl::id,
+                players::dsl::moniker,
+                players::dsl::avatar_url,
+                players::dsl::score,
+            ))
+            .load::<(
+                i32,
+                Option<i32>,
+                Option<i32>,
+                String,
+                String,
+                Option<String>,
+                i64,
+            )>(conn)
+            .with_context(|| format!("Failed to query {query_kind} leaderboard"))?;
+
+        Ok((total, entries))
+    })
+    .await?;
+
+    Ok(Json(LeaderboardResponse {
+        kind: player_kind,
+        epoch: None,
+        page,
+        per_page,
+        total,
+        entries: entries
+            .into_iter()
+            .map(
+                |(
+                    ranking,
+                    previous_ranking,
+                    ranking_delta,
+                    player_id,
+                    moniker,
+                    avatar_url,
+                    score,
+                )| {
+                    LeaderboardEntryResponse {
+                        ranking,
+                        previous_ranking,
+                        ranking_delta,
+                        player_id,
+                        moniker,
+                        avatar_url,
+                        score,
+                    }
+                },
+            )
+            .collect(),
+    }))
+}
+
+/// Serve the leaderboard from the latest score snapshot taken
+/// at or before the given epoch.
+async fn get_leaderboard_as_of(
+    cx: &Context,
+    player_kind: PlayerKindDb,
+    epoch: i32,
+    page: i64,
+    per_page: i64,
+) -> Result<Json<LeaderboardResponse>, ApiError> {
+    let not_found = ApiError::NotFound(format!("Score snapshot as of epoch {epoch}"));
+
+    let query_kind = player_kind.clone();
+    let Some((snapshot_epoch, total, entries)) = with_read_only_conn(cx, move |conn| {
+        use diesel::dsl::count_star;
+        use diesel::prelude::*;
+        use schema::players;
+        use schema::score_snapshots;
+
+        let Some(snapshot_epoch) = crate::snapshots::snapshot_epoch_as_of(conn, epoch)? else {
+            return Ok(None);
+        };
+
+        let ranked_players = score_snapshots::table
+            .inner_join(players::table)
+            .filter(score_snapshots::dsl::epoch.eq(snapshot_epoch))
+            .filter(score_snapshots::dsl::ranking.is_not_null())
+            .filter(players::dsl::kind.eq(&query_kind));
+
+        let total: i64 = ranked_players
+            .clone()
+            .select(count_star())
+            .first(conn)
+            .with_context(|| {
+                format!("Failed to count ranked {query_kind} players at epoch {snapshot_epoch}")
+            })?;
+
+        let entries = ranked_players
+            .order(score_snapshots::dsl::ranking)
+            .limit(per_page)
+            .offset((page - 1) * per_page)
+            .select((
+                score_snapshots::dsl::ranking.assume_not_null(),
+                players::dsl::id,
+                players::dsl::moniker,
+                players::dsl::avatar_url,
+                score_snapshots::dsl::score,
+            ))
+            .load::<(i32, String, String, Option<String>, i64)>(conn)
+            .with_context(|| {
+                format!("Failed to query {query_kind} leaderboard at epoch {snapshot_epoch}")
+            })?;
+
+        Ok(Some((snapshot_epoch, total, entries)))
+    })
+    .await?
+    else {
+        return Err(not_found);
+    };
+
+    Ok(Json(LeaderboardResponse {
+        kind: player_kind,
+        epoch: Some(snapshot_epoch),
+        page,
+        per_page,
+        total,
+        entries: entries
+            .into_iter()
+            .map(
+                |(ranking, player_id, moniker, avatar_url, score)| LeaderboardEntryResponse {
+                    ranking,
+                    // NB: snapshots do not track rank movements
+                    previous_ranking: None,
+                    ranking_delta: None,
+                    player_id,
+                    moniker,
+                    avatar_url,
+                    score,
+                },
+            )
+            .collect(),
+    }))
+}
+
+async fn get_player_task_decisions(
+    State(cx): State<Context>,
+    Path(player_id): Path<String>,
+    Query(pagination): Query<Pagination>,
+) -> Result<Json<Vec<TxTaskDecision>>, ApiError> {
+    let not_found = ApiError::NotFound(format!("Player {player_id}"));
+    let (page, per_page) = (pagination.page(), pagination.per_page());
+    let api_cx = cx.clone();
+
+    with_read_only_conn(&cx, move |conn| {
+        if !player_exists(conn, &player_id)? {
+            return Ok(None);
+        }
+        tasks::explain_player_task_decisions(
+            conn,
+            &api_cx,
+            &player_id,
+            (page - 1) * per_page,
+            per_page,
+        )
+        .map(Some)
+    })
+    .await?
+    .map(Json)
+    .ok_or(not_found)
+}
+
+async fn get_tx_task_decisions(
+    State(cx): State<Context>,
+    Path(hash): Path<String>,
+) -> Result<Json<Vec<TxTaskDecision>>, ApiError> {
+    let not_found = ApiError::NotFound(format!("Transaction {hash}"));
+    let api_cx = cx.clone();
+
+    let decisions = with_read_only_conn(&cx, move |conn| {
+        tasks::explain_tx_task_decisions(conn, &api_cx, &hash)
+    })
+    .await?;
+    if decisions.is_empty() {
+        return Err(not_found);
+    }
+    Ok(Json(decisions))
+}
+
+async fn get_status(State(cx): State<Context>) -> Result<Json<StatusResponse>, ApiError> {
+    let (last_processed_height, crawler_height, leader) = with_read_only_conn(&cx, |conn| {
+        Ok((
+            last_state::read_last_processed_tasks_block(conn)?,
+            last_state::read_last_crawled_block(conn)?,
+            leader::read_leader_lock_holder(conn)?,
+        ))
+    })
+    .await?;
+
+    Ok(Json(StatusResponse {
+        last_processed_height,
+        crawler_height,
+        lag: crawler_height
+            .map(|crawler_height| crawler_height - last_processed_height.unwrap_or_default()),
+        leader,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clamps_pagination() {
+        for ((page, per_page), (clamped_page, clamped_per_page)) in [
+            ((None, None), (1, DEFAULT_PAGE_SIZE)),
+            ((Some(0), Some(0)), (1, 1)),
+            ((Some(-5), Some(-5)), (1, 1)),
+            ((Some(3), Some(20)), (3, 20)),
+            ((Some(i64::MAX), Some(i64::MAX)), (MAX_PAGE, MAX_PAGE_SIZE)),
+        ] {
+            let pagination = Pagination { page, per_page };
+
+            assert_eq!(pagination.page(), clamped_page, "{pagination:?}");
+            assert_eq!(pagination.per_page(), clamped_per_page, "{pagination:?}");
+            assert!((pagination.page() - 1)
+                .checked_mul(pagination.per_page())
+                .is_some());
+        }
+    }
+}
@@ -0,0 +1,438 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Context as AnyhowContext};
+use namada_core::types::address::Address as NamadaAddress;
+use namada_core::types::hash::Hash as NamadaHash;
+use namada_core::types::storage::Epoch as NamadaEpoch;
+use serde::Deserialize;
+use shared::orm::tasks::TaskTypeDb;
+
+/// Version of the campaign configuration format understood by this binary.
+///
+/// NB: version 1 configs, which only listed `upgrade_votes` rules and
+/// took upgrade epochs from the command line, are rejected. Each of
+/// their rules must be rewritten as an `[[upgrades]]` entry, named
+/// `v0_to_v1` or `v1_to_v2` after the task of the rule, with the
+/// `epoch` of the upgrade and the same proposal ids, authors and
+/// grace epochs.
+pub const CAMPAIGN_CONFIG_VERSION: u32 = 2;
+
+/// Proposal ids voted on by pilots to complete the upgrade from v0
+/// to v1, before upgrade votes were made configurable.
+const LEGACY_V0_TO_V1_PROPOSAL_IDS: [u64; 2] = [316, 385];
+
+/// Name of the upgrade from v0 to v1 in the legacy campaign config.
+///
+/// NB: existing tasks were migrated to this name in the database.
+const LEGACY_V0_TO_V1_UPGRADE: &str = "v0_to_v1";
+
+/// Name of the upgrade from v1 to v2 in the legacy campaign config.
+///
+/// NB: existing tasks were migrated to this name in the database.
+const LEGACY_V1_TO_V2_UPGRADE: &str = "v1_to_v2";
+
+/// A network upgrade of the campaign, and the rules to identify
+/// the tasks bound to it.
+///
+/// A governance proposal is an upgrade vote if its id is explicitly
+/// listed in `proposal_ids`, or if both its author is in `authors` and
+/// its grace epoch is in `grace_epochs`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Upgrade {
+    /// Unique name of the upgrade, stored along with the tasks
+    /// completed for it.
+    pub name: String,
+    /// Epoch at which the network is upgraded.
+    pub epoch: u64,
+    /// Upgrade bound tasks that can be completed for this upgrade.
+    #[serde(default = "all_upgrade_bound_tasks")]
+    pub tasks: Vec<TaskTypeDb>,
+    #[serde(default)]
+    pub proposal_ids: BTreeSet<u64>,
+    #[serde(default)]
+    pub authors: BTreeSet<String>,
+    #[serde(default)]
+    pub grace_epochs: BTreeSet<u64>,
+}
+
+fn all_upgrade_bound_tasks() -> Vec<TaskTypeDb> {
+    TaskTypeDb::UPGRADE_BOUND.to_vec()
+}
+
+impl Upgrade {
+    /// Check if the given task can be completed for this upgrade.
+    pub fn offers(&self, task: TaskTypeDb) -> bool {
+        self.tasks.contains(&task)
+    }
+}
+
+/// Version of a campaign config, read ahead of the rest of the config
+/// since the fields of other versions are unknown.
+#[derive(Debug, Deserialize)]
+struct RawCampaignConfigVersion {
+    version: u32,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawCampaignConfig {
+    // NB: checked ahead, see `RawCampaignConfigVersion`
+    #[serde(rename = "version")]
+    _version: u32,
+    #[serde(default)]
+    upgrades: Vec<Upgrade>,
+}
+
+/// Campaign specific rules used to identify tasks.
+#[derive(Debug)]
+pub struct CampaignConfig {
+    /// Upgrades of the campaign, sorted by epoch.
+    upgrades: Vec<Upgrade>,
+    checksum: Option<String>,
+}
+
+impl CampaignConfig {
+    /// Load the campaign configuration from a TOML file.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read campaign config from {}", path.display()))?;
+        Self::parse_toml(&contents)
+            .with_context(|| format!("Failed to parse campaign config at {}", path.display()))
+    }
+
+    pub fn parse_toml(contents: &str) -> anyhow::Result<Self> {
+        let RawCampaignConfigVersion { version } =
+            toml::from_str(contents).context("Invalid TOML campaign config")?;
+
+        if version == 1 {
+            return Err(anyhow!(
+                "Campaign config version 1 is no longer supported, rewrite its upgrade votes \
+                 as upgrades of a version {CAMPAIGN_CONFIG_VERSION} config"
+            ));
+        }
+        if version != CAMPAIGN_CONFIG_VERSION {
+            return Err(anyhow!(
+                "Unsupported campaign config version {version}, expected {CAMPAIGN_CONFIG_VERSION}"
+            ));
+        }
+
+        let raw: RawCampaignConfig =
+            toml::from_str(contents).context("Invalid TOML campaign config")?;
+
+        let mut config = Self {
+            upgrades: raw.upgrades,
+            checksum: Some(NamadaHash::sha256(contents.as_bytes()).to_string()),
+        };
+        config.upgrades.sort_by_key(|upgrade| upgrade.epoch);
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    /// Build the campaign configuration used before it was made
+    /// configurable, from the upgrade proposer and upgrade epochs.
+    pub fn legacy(
+        upgrade_proposer: &NamadaAddress,
+        v0_to_v1: NamadaEpoch,
+        v1_to_v2: NamadaEpoch,
+    ) -> Self {
+        let authors = BTreeSet::from([upgrade_proposer.to_string()]);
+
+        Self {
+            upgrades: vec![
+                Upgrade {
+                    name: LEGACY_V0_TO_V1_UPGRADE.to_owned(),
+                    epoch: v0_to_v1.0,
+                    // NB: there was no v0 block to sign at the upgrade
+                    tasks: vec![
+                        TaskTypeDb::DelegateStakeBeforeUpgrade,
+                        TaskTypeDb::VoteUpgrade,
+                    ],
+                    proposal_ids: BTreeSet::from(LEGACY_V0_TO_V1_PROPOSAL_IDS),
+                    authors: authors.clone(),
+                    grace_epochs: BTreeSet::from([v0_to_v1.0]),
+                },
+                Upgrade {
+                    name: LEGACY_V1_TO_V2_UPGRADE.to_owned(),
+                    epoch: v1_to_v2.0,
+                    tasks: all_upgrade_bound_tasks(),
+                    proposal_ids: BTreeSet::new(),
+                    authors,
+                    grace_epochs: BTreeSet::from([v1_to_v2.0]),
+                },
+            ],
+            checksum: None,
+        }
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        let mut names = BTreeSet::new();
+
+        for upgrade in self.upgrades.iter() {
+            if upgrade.name.is_empty() {
+                return Err(anyhow!("Upgrade at epoch {} has no name", upgrade.epoch));
+            }
+            if !names.insert(upgrade.name.as_str()) {
+                return Err(anyhow!("Upgrade {} is listed more than once", upgrade.name));
+            }
+            if let Some(task) = upgrade.tasks.iter().find(|task| !task.is_upgrade_bound()) {
+                return Err(anyhow!(
+                    "Task {task:?} of upgrade {} is not bound to an upgrade",
+                    upgrade.name
+                ));
+            }
+            if upgrade.authors.is_empty() != upgrade.grace_epochs.is_empty() {
+                return Err(anyhow!(
+                    "Upgrade {} must list both authors and grace epochs, or neither of them",
+                    upgrade.name
+                ));
+            }
+        }
+
+        if let Some(pair) = self
+            .upgrades
+            .windows(2)
+            .find(|pair| pair[0].epoch == pair[1].epoch)
+        {
+            return Err(anyhow!(
+                "Upgrades {} and {} happen at the same epoch {}",
+                pair[0].name,
+                pair[1].name,
+                pair[0].epoch
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// SHA-256 checksum of the configuration's source, if it
+    /// was loaded from a file.
+    pub fn checksum(&self) -> Option<&str> {
+        self.checksum.as_deref()
+    }
+
+    /// Upgrades of the campaign, sorted by epoch.
+    pub fn upgrades(&self) -> &[Upgrade] {
+        &self.upgrades
+    }
+
+    /// Return the first upgrade happening after the given epoch.
+    pub fn next_upgrade_after(&self, epoch: u64) -> Option<&Upgrade> {
+        self.upgrades.iter().find(|upgrade| epoch < upgrade.epoch)
+    }
+
+    /// Return the upgrade whose accepted proposal ids include the
+    /// given proposal.
+    pub fn upgrade_vote_by_proposal_id(&self, proposal_id: u64) -> Option<&Upgrade> {
+        self.upgrades.iter().find(|upgrade| {
+            upgrade.offers(TaskTypeDb::VoteUpgrade) && upgrade.proposal_ids.contains(&proposal_id)
+        })
+    }
+
+    /// Return the upgrade matching the author and grace epoch of
+    /// a proposal.
+    pub fn upgrade_vote_by_author_and_grace_epoch(
+        &self,
+        author: &str,
+        grace_epoch: u64,
+    ) -> Option<&Upgrade> {
+        self.upgrades.iter().find(|upgrade| {
+            upgrade.offers(TaskTypeDb::VoteUpgrade)
+                && upgrade.authors.contains(author)
+                && upgrade.grace_epochs.contains(&grace_epoch)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AUTHOR: &str = "tnam1qproposer";
+
+    /// Upgrades at epochs 10 and 20, listed out of order.
+    fn campaign() -> CampaignConfig {
+        CampaignConfig::parse_toml(&format!(
+            r#"
+            version = 2
+
+            [[upgrades]]
+            name = "v1_to_v2"
+            epoch = 20
+            authors = ["{AUTHOR}"]
+            grace_epochs = [20, 21]
+
+            [[upgrades]]
+            name = "v0_to_v1"
+            epoch = 10
+            tasks = ["DelegateStakeBeforeUpgrade", "VoteUpgrade"]
+            proposal_ids = [316, 385]
+
+            [[upgrades]]
+            name = "v2_to_v3"
+            epoch = 30
+            tasks = ["SignFirstBlockOfUpgrade"]
+            proposal_ids = [400]
+            "#
+        ))
+        .expect("Campaign config should be valid")
+    }
+
+    fn upgrade_name(upgrade: Option<&Upgrade>) -> Option<&str> {
+        upgrade.map(|upgrade| upgrade.name.as_str())
+    }
+
+    #[test]
+    fn sorts_upgrades_by_epoch() {
+        let campaign = campaign();
+        let names: Vec<_> = campaign
+            .upgrades()
+            .iter()
+            .map(|upgrade| upgrade.name.as_str())
+            .collect();
+
+        assert_eq!(names, ["v0_to_v1", "v1_to_v2", "v2_to_v3"]);
+        assert!(campaign.checksum().is_some());
+    }
+
+    #[test]
+    fn rejects_invalid_campaign_configs() {
+        for (upgrade, error) in [
+            ("name = \"\"\nepoch = 1", "has no name"),
+            (
+                "name = \"a\"\nepoch = 1\ntasks = [\"ClaimPosRewards\"]",
+                "is not bound to an upgrade",
+            ),
+            (
+                "name = \"a\"\nepoch = 1\nauthors = [\"tnam1q\"]",
+                "must list both authors and grace epochs",
+            ),
+            (
+                "name = \"a\"\nepoch = 1\ngrace_epochs = [1]",
+                "must list both authors and grace epochs",
+            ),
+            (
+                "name = \"a\"\nepoch = 1\nunknown = 1",
+                "Invalid TOML campaign config",
+            ),
+        ] {
+            let contents = format!("version = 2\n[[upgrades]]\n{upgrade}");
+            let err = CampaignConfig::parse_toml(&contents).unwrap_err();
+
+            assert!(format!("{err:#}").contains(error), "{upgrade}: {err:#}");
+        }
+    }
+
+    #[test]
+    fn rejects_conflicting_upgrades() {
+        for (upgrades, error) in [
+            (
+                "[[upgrades]]\nname = \"a\"\nepoch = 1\n[[upgrades]]\nname = \"a\"\nepoch = 2",
+                "Upgrade a is listed more than once",
+            ),
+            (
+                "[[upgrades]]\nname = \"a\"\nepoch = 1\n[[upgrades]]\nname = \"b\"\nepoch = 1",
+                "Upgrades a and b happen at the same epoch 1",
+            ),
+        ] {
+            let err = CampaignConfig::parse_toml(&format!("version = 2\n{upgrades}")).unwrap_err();
+
+            assert!(format!("{err:#}").contains(error), "{upgrades}: {err:#}");
+        }
+    }
+
+    #[test]
+    fn rejects_unsupported_campaign_config_versions() {
+        for (contents, error) in [
+            (
+                "version = 1\n[[upgrade_votes]]\ntask = \"VoteUpgradeV0ToV1\"\nproposal_ids = [316]",
+                "Campaign config version 1 is no longer supported",
+            ),
+            ("version = 3", "Unsupported campaign config version 3"),
+        ] {
+            let err = CampaignConfig::parse_toml(contents).unwrap_err();
+
+            assert!(format!("{err:#}").contains(error), "{contents}: {err:#}");
+        }
+    }
+
+    #[test]
+    fn finds_next_upgrade_after_epoch() {
+        let campaign = campaign();
+
+        for (epoch, upgrade) in [
+            (0, Some("v0_to_v1")),
+            (9, Some("v0_to_v1")),
+            // NB: bonds made at the epoch of an upgrade count toward the next one
+            (10, Some("v1_to_v2")),
+            (19, Some("v1_to_v2")),
+            (20, Some("v2_to_v3")),
+            (30, None),
+            (u64::MAX, None),
+        ] {
+            assert_eq!(
+                upgrade_name(campaign.next_upgrade_after(epoch)),
+                upgrade,
+                "epoch {epoch}"
+            );
+        }
+    }
+
+    #[test]
+    fn finds_upgrade_vote_by_proposal_id() {
+        let campaign = campaign();
+
+        for (proposal_id, upgrade) in [
+            (316, Some("v0_to_v1")),
+            (385, Some("v0_to_v1")),
+            (317, None),
+            // NB: the upgrade does not offer the vote task
+            (400, None),
+        ] {
+            assert_eq!(
+                upgrade_name(campaign.upgrade_vote_by_proposal_id(proposal_id)),
+                upgrade,
+                "proposal {proposal_id}"
+            );
+        }
+    }
+
+    #[test]
+    fn finds_upgrade_vote_by_author_and_grace_epoch() {
+        let campaign = campaign();
+
+        for (author, grace_epoch, upgrade) in [
+            (AUTHOR, 20, Some("v1_to_v2")),
+            // last grace epoch of the upgrade
+            (AUTHOR, 21, Some("v1_to_v2")),
+            (AUTHOR, 19, None),
+            (AUTHOR, 22, None),
+            ("tnam1qother", 20, None),
+        ] {
+            assert_eq!(
+                upgrade_name(campaign.upgrade_vote_by_author_and_grace_epoch(author, grace_epoch)),
+                upgrade,
+                "{author} at grace epoch {grace_epoch}"
+            );
+        }
+    }
+
+    #[test]
+    fn legacy_campaign_config_matches_upgrade_votes() {
+        let proposer: NamadaAddress = AUTHOR.parse().expect("Address should be valid");
+        let campaign = CampaignConfig::legacy(&proposer, NamadaEpoch(10), NamadaEpoch(20));
+
+        assert_eq!(
+            upgrade_name(campaign.upgrade_vote_by_proposal_id(316)),
+            Some(LEGACY_V0_TO_V1_UPGRADE)
+        );
+        assert_eq!(
+            upgrade_name(campaign.upgrade_vote_by_author_and_grace_epoch(AUTHOR, 20)),
+            Some(LEGACY_V1_TO_V2_UPGRADE)
+        );
+        assert!(campaign.checksum().is_none());
+    }
+}
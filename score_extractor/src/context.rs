@@ -6,30 +6,44 @@ use std::sync::Mutex;
 
 use anyhow::Context as AnyhowContext;
 use namada_core::types::address::Address as NamadaAddress;
-use namada_core::types::storage::Epoch as NamadaEpoch;
 use namada_sdk::rpc::query_native_token;
+use shared::memo::MemoParser;
 use shared::orm::players::PlayerKindDb;
 use shared::orm::schema;
 use tendermint_rpc::HttpClient;
 use tokio::time;
 
+use crate::campaign::CampaignConfig;
 use crate::db;
+use crate::prizes::PrizeSchedule;
+use crate::scores::RecomputeStrategy;
 
 #[derive(Clone)]
 pub struct Context {
     db_connection_pool: db::Pool,
     address_book: AddressBook,
     genesis_time: Option<chrono::NaiveDateTime>,
-    epochs: Epochs,
     player_kinds: PlayerKinds,
+    prize_schedule: Arc<PrizeSchedule>,
+    campaign: Arc<CampaignConfig>,
+    uptime_window: UptimeWindow,
+    recompute_strategy: RecomputeStrategy,
+    memo_parser: Arc<MemoParser>,
+    /// Id of the Postgres backend holding the leader lock on behalf
+    /// of this replica, if it is the leader.
+    leader_backend_pid: Arc<Mutex<Option<i32>>>,
 }
 
 impl fmt::Debug for Context {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Context")
             .field("genesis_time", &self.genesis_time)
-            .field("epochs", &self.epochs)
             .field("address_book", &self.address_book)
+            .field("prize_schedule", &self.prize_schedule.checksum())
+            .field("campaign", &self.campaign)
+            .field("uptime_window", &self.uptime_window)
+            .field("recompute_strategy", &self.recompute_strategy)
+            .field("memo_formats", &self.memo_parser.format_names())
             .finish_non_exhaustive()
     }
 }
@@ -80,12 +94,6 @@ impl PlayerKinds {
     }
 }
 
-#[derive(Clone, Debug)]
-pub struct Epochs {
-    pub v0_to_v1: NamadaEpoch,
-    pub v1_to_v2: NamadaEpoch,
-}
-
 #[derive(Debug)]
 pub struct AddressBook {
     inner: Arc<Addresses>,
@@ -110,58 +118,80 @@ impl Deref for AddressBook {
 #[derive(Debug)]
 pub struct Addresses {
     pub naan: NamadaAddress,
-    pub upgrade_proposer: NamadaAddress,
 }
 
-pub struct GenesisTime(pub Option<chrono::NaiveDateTime>);
+/// Range of blocks over which the uptime of pilots is computed.
+///
+/// Windows always end at the latest block indexed by the crawler.
+#[derive(Debug, Copy, Clone, Default, clap::ValueEnum)]
+pub enum UptimeWindow {
+    /// Start counting blocks from genesis.
+    FromGenesis,
+    /// Start counting blocks from the first epoch a pilot's validator
+    /// entered the validator set.
+    #[default]
+    FromValidatorSetEntry,
+}
 
-pub struct UpgradeProposer(pub NamadaAddress);
+pub struct GenesisTime(pub Option<chrono::NaiveDateTime>);
 
 pub struct DatabaseUrl(pub String);
 
 pub struct CometBftUrl(pub String);
 
+/// Source of the address of the native token.
+pub enum NativeToken {
+    /// The address is known ahead of time.
+    Address(NamadaAddress),
+    /// The address is queried from a CometBFT node.
+    QueryCometBft(CometBftUrl),
+}
+
 impl Context {
     pub async fn new(
-        epochs: Epochs,
-        UpgradeProposer(upgrade_proposer): UpgradeProposer,
         GenesisTime(genesis_time): GenesisTime,
         DatabaseUrl(database_url): DatabaseUrl,
-        CometBftUrl(cometbft_url): CometBftUrl,
+        native_token: NativeToken,
+        prize_schedule: PrizeSchedule,
+        campaign: CampaignConfig,
+        uptime_window: UptimeWindow,
+        recompute_strategy: RecomputeStrategy,
     ) -> anyhow::Result<Self> {
-        tracing::debug!(cometbft_url, "Connecting to CometBFT");
-        let client =
-            HttpClient::new(&*cometbft_url).context("Failed to instantiate CometBFT RPC client")?;
-        let naan = loop {
-            if let Ok(token) = query_native_token(&client).await {
-                break token;
+        let naan = match native_token {
+            NativeToken::Address(naan) => {
+                tracing::debug!(%naan, "Using the given NAAN token address");
+                naan
+            }
+            NativeToken::QueryCometBft(CometBftUrl(cometbft_url)) => {
+                query_naan(&cometbft_url).await?
             }
-            const RETRY_SLEEP: time::Duration = time::Duration::from_secs(30);
-            tracing::warn!(
-                retry_sleep = ?RETRY_SLEEP,
-                cometbft_url,
-                "Failed to query NAAN token address, retrying"
-            );
-            time::sleep(RETRY_SLEEP).await;
         };
         let address_book = AddressBook {
-            inner: Arc::new(Addresses {
-                naan,
-                upgrade_proposer,
-            }),
+            inner: Arc::new(Addresses { naan }),
         };
-        tracing::debug!(?address_book, "Fetched token address book from CometBFT");
+        tracing::debug!(?address_book, "Built token address book");
         tracing::debug!(database_url, "Connecting to Postgres");
         let db_connection_pool = db::Pool::new(database_url).await?;
         Ok(Self {
             db_connection_pool,
             address_book,
             genesis_time,
-            epochs,
             player_kinds: PlayerKinds::new(),
+            prize_schedule: Arc::new(prize_schedule),
+            campaign: Arc::new(campaign),
+            uptime_window,
+            recompute_strategy,
+            memo_parser: Arc::new(MemoParser::default()),
+            leader_backend_pid: Arc::new(Mutex::new(None)),
         })
     }
 
+    /// Parse memos with the given parser, instead of the default one.
+    pub fn with_memo_parser(mut self, memo_parser: MemoParser) -> Self {
+        self.memo_parser = Arc::new(memo_parser);
+        self
+    }
+
     pub fn db_connection_pool(&self) -> &db::Pool {
         &self.db_connection_pool
     }
@@ -174,11 +204,58 @@ impl Context {
         self.genesis_time.as_ref()
     }
 
-    pub fn epochs(&self) -> &Epochs {
-        &self.epochs
-    }
-
     pub fn player_kinds(&self) -> &PlayerKinds {
         &self.player_kinds
     }
+
+    pub fn prize_schedule(&self) -> &PrizeSchedule {
+        &self.prize_schedule
+    }
+
+    pub fn campaign(&self) -> &CampaignConfig {
+        &self.campaign
+    }
+
+    pub fn uptime_window(&self) -> UptimeWindow {
+        self.uptime_window
+    }
+
+    pub fn recompute_strategy(&self) -> RecomputeStrategy {
+        self.recompute_strategy
+    }
+
+    pub fn memo_parser(&self) -> &MemoParser {
+        &self.memo_parser
+    }
+
+    /// Id of the Postgres backend holding the leader lock on behalf
+    /// of this replica, checked by write transactions.
+    pub fn leader_backend_pid(&self) -> Option<i32> {
+        *self.leader_backend_pid.lock().unwrap()
+    }
+
+    pub fn set_leader_backend_pid(&self, backend_pid: Option<i32>) {
+        *self.leader_backend_pid.lock().unwrap() = backend_pid;
+    }
+}
+
+/// Query the address of the native token from CometBFT, retrying
+/// until the node answers.
+async fn query_naan(cometbft_url: &str) -> anyhow::Result<NamadaAddress> {
+    tracing::debug!(cometbft_url, "Connecting to CometBFT");
+    let client =
+        HttpClient::new(cometbft_url).context("Failed to instantiate CometBFT RPC client")?;
+    loop {
+        if let Ok(token) = query_native_token(&client).await {
+            tracing::debug!(naan = %token, "Fetched NAAN token address from CometBFT");
+            return Ok(token);
+        }
+        const RETRY_SLEEP: time::Duration = time::Duration::from_secs(30);
+        tracing::warn!(
+            retry_sleep = ?RETRY_SLEEP,
+            cometbft_url,
+            "Failed to query NAAN token address, retrying"
+        );
+        time::sleep(RETRY_SLEEP).await;
+    }
 }
@@ -0,0 +1,30 @@
+use std::borrow::Cow;
+
+/// Escape a field of a CSV row, quoting it if it contains
+/// separators, quotes or line breaks.
+pub fn escape(field: &str) -> Cow<'_, str> {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\"")).into()
+    } else {
+        field.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_fields() {
+        for (field, escaped) in [
+            ("tpknam1abc", "tpknam1abc"),
+            ("", ""),
+            ("a,b", "\"a,b\""),
+            ("say \"hi\"", "\"say \"\"hi\"\"\""),
+            ("line\nbreak", "\"line\nbreak\""),
+            ("carriage\rreturn", "\"carriage\rreturn\""),
+        ] {
+            assert_eq!(escape(field), escaped, "{field:?}");
+        }
+    }
+}
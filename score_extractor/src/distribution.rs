@@ -0,0 +1,214 @@
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Context as AnyhowContext};
+use namada_core::types::hash::Hash as NamadaHash;
+use shared::orm::players::PlayerKindDb;
+use shared::orm::schema;
+
+use crate::csv;
+use crate::db;
+
+/// How to handle the token units left over after rounding down
+/// every player's allocation.
+#[derive(Debug, Copy, Clone, Default, clap::ValueEnum)]
+pub enum RemainderPolicy {
+    /// Hand out one unit to each of the players with the largest
+    /// fractional parts, until the pool is exhausted.
+    #[default]
+    LargestRemainder,
+    /// Give the whole remainder to the player with the highest score.
+    TopScorer,
+    /// Leave the remainder undistributed.
+    Discard,
+}
+
+/// Parameters of a reward distribution.
+#[derive(Debug, Clone)]
+pub struct DistributionParams {
+    /// Total amount of tokens to distribute, in the token's base units.
+    pub total_pool: u128,
+    /// Minimum score a player must have to receive an allocation.
+    pub min_score: i64,
+    pub remainder_policy: RemainderPolicy,
+    /// Alias of the distributed token in the genesis files.
+    pub token_alias: String,
+    /// Number of decimal places of the distributed token.
+    pub denomination: u8,
+}
+
+#[derive(Debug)]
+pub struct Allocation {
+    pub player_id: String,
+    pub address: String,
+    pub kind: PlayerKindDb,
+    pub score: i64,
+    pub amount: u128,
+}
+
+/// Token allocations of all eligible players.
+#[derive(Debug)]
+pub struct Distribution {
+    /// Allocations, sorted by player id.
+    pub allocations: Vec<Allocation>,
+    /// Amount of tokens from the pool that were not allocated.
+    pub undistributed: u128,
+}
+
+/// Compute the allocation of each player with a score of at least
+/// `min_score`, proportionally to their score.
+pub fn compute_distribution(
+    conn: &mut db::Connection,
+    params: &DistributionParams,
+) -> anyhow::Result<Distribution> {
+    use diesel::prelude::*;
+    use schema::players;
+
+    let eligible_players = players::table
+        .filter(
+            players::dsl::score
+                .ge(params.min_score)
+                .and(players::dsl::score.gt(0))
+                .and(players::dsl::is_banned.ne(true)),
+        )
+        .order(players::dsl::id)
+        .select((
+            players::dsl::id,
+            players::dsl::namada_player_address,
+            players::dsl::kind,
+            players::dsl::score,
+        ))
+        .load::<(String, String, PlayerKindDb, i64)>(conn)
+        .context("Failed to fetch eligible players from db")?;
+
+    let total_score: u128 = eligible_players
+        .iter()
+        .map(|(_, _, _, score)| *score as u128)
+        .sum();
+
+    if total_score == 0 {
+        tracing::warn!("No players are eligible for the distribution");
+        return Ok(Distribution {
+            allocations: vec![],
+            undistributed: params.total_pool,
+        });
+    }
+
+    let mut remainders = Vec::with_capacity(eligible_players.len());
+    let mut allocations = eligible_players
+        .into_iter()
+        .enumerate()
+        .map(|(index, (player_id, address, kind, score))| {
+            let weighted_pool = params
+                .total_pool
+                .checked_mul(score as u128)
+                .ok_or_else(|| anyhow!("Overflow computing the allocation of {player_id}"))?;
+            remainders.push((weighted_pool % total_score, index));
+            Ok(Allocation {
+                player_id,
+                address,
+                kind,
+                score,
+                amount: weighted_pool / total_score,
+            })
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let allocated: u128 = allocations.iter().map(|allocation| allocation.amount).sum();
+    let mut undistributed = params.total_pool - allocated;
+
+    match params.remainder_policy {
+        RemainderPolicy::LargestRemainder => {
+            // NB: ties are broken by player id, since allocations
+            // are sorted by player id
+            remainders.sort_unstable_by(|(rem_a, index_a), (rem_b, index_b)| {
+                rem_b.cmp(rem_a).then(index_a.cmp(index_b))
+            });
+            for (_, index) in remainders.into_iter().take(undistributed as usize) {
+                allocations[index].amount += 1;
+                undistributed -= 1;
+            }
+        }
+        RemainderPolicy::TopScorer => {
+            let top_scorer = allocations
+                .iter_mut()
+                .reduce(|top, allocation| {
+                    if allocation.score > top.score {
+                        allocation
+                    } else {
+                        top
+                    }
+                })
+                .expect("There is at least one eligible player");
+            top_scorer.amount += undistributed;
+            undistributed = 0;
+        }
+        RemainderPolicy::Discard => {}
+    }
+
+    tracing::info!(
+        no_of_players = allocations.len(),
+        total_pool = %params.total_pool,
+        undistributed = %undistributed,
+        "Computed reward distribution"
+    );
+
+    Ok(Distribution {
+        allocations,
+        undistributed,
+    })
+}
+
+impl Distribution {
+    /// Render the distribution as a Namada genesis `balances.toml`.
+    ///
+    /// Allocations to the same address are summed.
+    pub fn to_balances_toml(&self, params: &DistributionParams) -> anyhow::Result<String> {
+        let mut balances: BTreeMap<&str, u128> = BTreeMap::new();
+        for allocation in self.allocations.iter() {
+            *balances.entry(&allocation.address).or_default() += allocation.amount;
+        }
+
+        let balances: BTreeMap<_, _> = balances
+            .into_iter()
+            .map(|(address, amount)| (address, format_amount(amount, params.denomination)))
+            .collect();
+        let token = BTreeMap::from([(params.token_alias.as_str(), balances)]);
+        let file = BTreeMap::from([("token", token)]);
+
+        toml::to_string(&file).context("Failed to serialize balances to TOML")
+    }
+
+    /// Render the distribution as CSV, with one row per player.
+    pub fn to_csv(&self, params: &DistributionParams) -> String {
+        let mut csv = String::from("player_id,address,kind,score,amount\n");
+        for allocation in self.allocations.iter() {
+            csv.push_str(&format!(
+                "{},{},{},{},{}\n",
+                csv::escape(&allocation.player_id),
+                csv::escape(&allocation.address),
+                allocation.kind,
+                allocation.score,
+                format_amount(allocation.amount, params.denomination),
+            ));
+        }
+        csv
+    }
+}
+
+/// SHA-256 checksum of an exported distribution file.
+pub fn checksum(contents: &str) -> String {
+    NamadaHash::sha256(contents.as_bytes()).to_string()
+}
+
+fn format_amount(amount: u128, denomination: u8) -> String {
+    if denomination == 0 {
+        return amount.to_string();
+    }
+    let unit = 10u128.pow(denomination as u32);
+    format!(
+        "{}.{:0width$}",
+        amount / unit,
+        amount % unit,
+        width = denomination as usize
+    )
+}
@@ -0,0 +1,23 @@
+use std::ops::RangeInclusive;
+
+use diesel::pg::Pg;
+use diesel::sql_types::Integer;
+use shared::orm::schema;
+
+/// Query the ids of the governance proposals whose voting period
+/// overlapped with the given range of epochs.
+pub fn eligible_proposals<'a>(
+    epochs: &RangeInclusive<i32>,
+) -> schema::governance_proposals::BoxedQuery<'a, Pg, Integer> {
+    use diesel::prelude::*;
+    use schema::governance_proposals::dsl::*;
+
+    governance_proposals
+        .filter(
+            start_epoch
+                .le(*epochs.end())
+                .and(end_epoch.ge(*epochs.start())),
+        )
+        .select(id)
+        .into_boxed()
+}
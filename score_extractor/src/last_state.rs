@@ -56,23 +56,40 @@ pub fn update_last_processed_tasks_block(
     Ok(())
 }
 
-fn read_last_processed_task_heights(conn: &mut db::Connection) -> anyhow::Result<(i32, i32)> {
-    use diesel::dsl::max;
+/// Read the height of the last block whose tasks have been processed.
+pub fn read_last_processed_tasks_block(conn: &mut db::Connection) -> anyhow::Result<Option<i32>> {
     use diesel::prelude::*;
-    use schema::crawler_state::dsl::*;
     use schema::task_completion_state::dsl::*;
 
-    let our_height = task_completion_state
+    task_completion_state
         .select(last_processed_height)
         .first::<i32>(conn)
         .optional()
-        .context("Failed to query last processed task completion height")?
-        .unwrap_or(0);
-    let crawler_height = crawler_state
+        .context("Failed to query last processed task completion height")
+}
+
+/// Read the height of the last block indexed by the crawler.
+pub fn read_last_crawled_block(conn: &mut db::Connection) -> anyhow::Result<Option<i32>> {
+    use diesel::dsl::max;
+    use diesel::prelude::*;
+    use schema::crawler_state::dsl::*;
+
+    crawler_state
         .select(max(height))
         .first::<Option<i32>>(conn)
-        .context("Failed to query last processed crawler height")?
-        .unwrap_or(0);
+        .context("Failed to query last processed crawler height")
+}
+
+/// Check if the crawler has indexed blocks whose tasks have
+/// yet to be processed.
+pub fn has_unprocessed_blocks(conn: &mut db::Connection) -> anyhow::Result<bool> {
+    let (our_height, crawler_height) = read_last_processed_task_heights(conn)?;
+    Ok(our_height < crawler_height)
+}
+
+fn read_last_processed_task_heights(conn: &mut db::Connection) -> anyhow::Result<(i32, i32)> {
+    let our_height = read_last_processed_tasks_block(conn)?.unwrap_or(0);
+    let crawler_height = read_last_crawled_block(conn)?.unwrap_or(0);
 
     tracing::debug!(
         our_height,
@@ -0,0 +1,231 @@
+use std::env;
+
+use anyhow::Context;
+use diesel::sql_types::{BigInt, Bool, Integer, Nullable, Text, Timestamptz};
+use serde::Serialize;
+
+use crate::db;
+
+/// Key of the session level advisory lock held by the replica
+/// in charge of updating the database.
+pub const LEADER_LOCK_KEY: i64 = 0x7363_6f72;
+
+/// Key of the transaction level advisory lock serializing the write
+/// transactions of all replicas.
+pub const WRITE_LOCK_KEY: i64 = 0x7363_6f73;
+
+/// Replica of the score extractor currently holding the leader lock.
+#[derive(Debug, Serialize, diesel::QueryableByName)]
+pub struct LeaderLockHolder {
+    /// Name the holder connected to Postgres with.
+    #[diesel(sql_type = Nullable<Text>)]
+    pub replica: Option<String>,
+    /// Id of the Postgres backend serving the holder.
+    #[diesel(sql_type = Integer)]
+    pub backend_pid: i32,
+    #[diesel(sql_type = Nullable<Text>)]
+    pub client_addr: Option<String>,
+    #[diesel(sql_type = Nullable<Timestamptz>)]
+    pub connected_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Advisory lock electing a single replica to update the database,
+/// while the others stand by.
+///
+/// The lock is held over a dedicated connection, rather than a pooled
+/// one, so that it outlives the transactions of update cycles. If the
+/// leader dies, Postgres drops its session along with the lock, and a
+/// standby takes over on its next cycle. Since the session may also
+/// drop mid-cycle, write transactions check that it still holds the
+/// lock with [`ensure_leader`].
+pub struct LeaderLock {
+    database_url: String,
+    replica_name: String,
+    /// Dedicated connection, along with the id of the Postgres
+    /// backend serving it.
+    conn: Option<(db::Connection, i32)>,
+    /// Whether this replica led on the previous attempt, if
+    /// there was one.
+    leading: Option<bool>,
+}
+
+impl LeaderLock {
+    pub fn new(database_url: String) -> Self {
+        let hostname = env::var("HOSTNAME").unwrap_or_else(|_| "localhost".to_owned());
+        let replica_name = format!("score_extractor@{hostname}:{}", std::process::id());
+        Self {
+            database_url,
+            replica_name,
+            conn: None,
+            leading: None,
+        }
+    }
+
+    /// Name identifying this replica to Postgres.
+    pub fn replica_name(&self) -> &str {
+        &self.replica_name
+    }
+
+    /// Id of the Postgres backend holding the leader lock on behalf
+    /// of this replica, if it led on the previous attempt.
+    pub fn backend_pid(&self) -> Option<i32> {
+        match (&self.conn, self.leading) {
+            (Some((_, backend_pid)), Some(true)) => Some(*backend_pid),
+            _ => None,
+        }
+    }
+
+    /// Check if this replica is the leader, acquiring the lock if
+    /// no other replica holds it.
+    pub fn try_lead(&mut self) -> anyhow::Result<bool> {
+        let (conn, backend_pid) = match &mut self.conn {
+            Some(conn) => conn,
+            conn @ None => conn.insert(connect(&self.database_url, &self.replica_name)?),
+        };
+        let result = acquire(conn, *backend_pid).and_then(|leading| {
+            if leading {
+                if self.leading != Some(true) {
+                    tracing::info!(
+                        replica = self.replica_name,
+                        "Acquired leader lock, this replica now updates the database"
+                    );
+                }
+                return Ok(true);
+            }
+            let holder = read_leader_lock_holder(conn)?;
+            if self.leading == Some(false) {
+                tracing::debug!(?holder, "Another replica holds the leader lock");
+            } else {
+                tracing::info!(
+                    replica = self.replica_name,
+                    ?holder,
+                    "Another replica holds the leader lock, standing by"
+                );
+            }
+            Ok(false)
+        });
+        if result.is_err() {
+            // NB: drop the connection, in case it is broken; if
+            // we held the lock, Postgres releases it as well
+            self.conn = None;
+        }
+        self.leading = result.as_ref().ok().copied();
+        result
+    }
+}
+
+fn connect(database_url: &str, replica_name: &str) -> anyhow::Result<(db::Connection, i32)> {
+    use diesel::dsl::sql;
+    use diesel::prelude::*;
+
+    let mut conn = db::Connection::establish(database_url)
+        .context("Failed to open leader lock connection to Postgres")?;
+    diesel::sql_query("SELECT set_config('application_name', $1, false)")
+        .bind::<Text, _>(replica_name)
+        .execute(&mut conn)
+        .context("Failed to set the application name of the leader lock connection")?;
+    let backend_pid = diesel::select(sql::<Integer>("pg_backend_pid()"))
+        .get_result::<i32>(&mut conn)
+        .context("Failed to query the backend of the leader lock connection")?;
+    Ok((conn, backend_pid))
+}
+
+fn acquire(conn: &mut db::Connection, backend_pid: i32) -> anyhow::Result<bool> {
+    use diesel::dsl::sql;
+    use diesel::prelude::*;
+
+    // NB: advisory locks are reentrant, so we must not take the
+    // lock again if we already hold it
+    if backend_holds_leader_lock(conn, backend_pid)? {
+        return Ok(true);
+    }
+    diesel::select(
+        sql::<Bool>("pg_try_advisory_lock(")
+            .bind::<BigInt, _>(LEADER_LOCK_KEY)
+            .sql(")"),
+    )
+    .get_result::<bool>(conn)
+    .context("Failed to try acquiring the leader lock")
+}
+
+/// Make sure that, from within a write transaction, this replica
+/// still holds the leader lock over the session of the given backend.
+///
+/// The transaction first waits for the write transactions of other
+/// replicas to finish, so that a replica that lost the lock halfway
+/// through an update cycle can not write alongside the standby that
+/// took over.
+pub fn ensure_leader(conn: &mut db::Connection, leader_pid: Option<i32>) -> anyhow::Result<()> {
+    use diesel::prelude::*;
+
+    let Some(leader_pid) = leader_pid else {
+        anyhow::bail!("This replica does not hold the leader lock");
+    };
+    diesel::sql_query("SELECT pg_advisory_xact_lock($1)")
+        .bind::<BigInt, _>(WRITE_LOCK_KEY)
+        .execute(conn)
+        .context("Failed to acquire the write lock")?;
+    if !backend_holds_leader_lock(conn, leader_pid)? {
+        anyhow::bail!("This replica lost the leader lock, aborting database updates");
+    }
+    Ok(())
+}
+
+fn backend_holds_leader_lock(conn: &mut db::Connection, backend_pid: i32) -> anyhow::Result<bool> {
+    use diesel::dsl::sql;
+    use diesel::prelude::*;
+
+    diesel::select(
+        sql::<Bool>(
+            r#"
+            EXISTS (
+              SELECT 1 FROM pg_locks
+              WHERE locktype = 'advisory'
+                AND classid = 0
+                AND objid::BIGINT = "#,
+        )
+        .bind::<BigInt, _>(LEADER_LOCK_KEY)
+        .sql(
+            r#"
+                AND objsubid = 1
+                AND pid = "#,
+        )
+        .bind::<Integer, _>(backend_pid)
+        .sql(
+            r#"
+                AND granted
+            )
+            "#,
+        ),
+    )
+    .get_result::<bool>(conn)
+    .context("Failed to check if we hold the leader lock")
+}
+
+/// Return the replica currently holding the leader lock, if any.
+pub fn read_leader_lock_holder(
+    conn: &mut db::Connection,
+) -> anyhow::Result<Option<LeaderLockHolder>> {
+    use diesel::prelude::*;
+
+    diesel::sql_query(
+        r#"
+        SELECT
+          pg_stat_activity.application_name AS replica,
+          pg_stat_activity.pid AS backend_pid,
+          pg_stat_activity.client_addr::TEXT AS client_addr,
+          pg_stat_activity.backend_start AS connected_at
+        FROM pg_locks
+        JOIN pg_stat_activity ON pg_stat_activity.pid = pg_locks.pid
+        WHERE pg_locks.locktype = 'advisory'
+          AND pg_locks.classid = 0
+          AND pg_locks.objid::BIGINT = $1
+          AND pg_locks.objsubid = 1
+          AND pg_locks.granted
+        "#,
+    )
+    .bind::<BigInt, _>(LEADER_LOCK_KEY)
+    .get_result(conn)
+    .optional()
+    .context("Failed to query the holder of the leader lock")
+}
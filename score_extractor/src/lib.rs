@@ -1,8 +1,19 @@
+pub mod api;
+pub mod campaign;
 pub mod context;
+pub mod csv;
 pub mod db;
+pub mod distribution;
+pub mod governance;
 pub mod last_state;
+pub mod leader;
+pub mod memos;
+pub mod metrics;
+pub mod notifications;
 pub mod players;
+pub mod prizes;
 pub mod scores;
+pub mod snapshots;
 pub mod sql_ext;
 pub mod tasks;
 pub mod transactions;
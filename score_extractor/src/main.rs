@@ -1,21 +1,34 @@
 use anyhow::Context as AnyhowContext;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+
 use clap::Parser;
 use clap_verbosity_flag::{InfoLevel, LevelFilter, Verbosity};
 use diesel::result::Error as DieselErr;
 use namada_core::types::address::Address as NamadaAddress;
 use namada_core::types::storage::Epoch as NamadaEpoch;
+use score_extractor::api;
+use score_extractor::campaign::CampaignConfig;
 use score_extractor::context::{
-    CometBftUrl, Context, DatabaseUrl, Epochs, GenesisTime, UpgradeProposer,
+    CometBftUrl, Context, DatabaseUrl, GenesisTime, NativeToken, UptimeWindow,
 };
+use score_extractor::csv;
 use score_extractor::db;
+use score_extractor::distribution::{self, DistributionParams, RemainderPolicy};
 use score_extractor::last_state;
+use score_extractor::leader::{self, LeaderLock};
+use score_extractor::memos;
+use score_extractor::metrics::{self, PendingMetrics};
+use score_extractor::notifications;
 use score_extractor::players;
-use score_extractor::scores;
+use score_extractor::prizes::PrizeSchedule;
+use score_extractor::scores::{self, RecomputeStrategy};
+use score_extractor::snapshots;
 use score_extractor::tasks;
 use score_extractor::transactions;
-use shared::player::PlayerId;
 use tokio::signal;
-use tokio::sync::oneshot;
+use tokio::sync::{oneshot, Notify};
 use tokio::time;
 use tracing::Level;
 use tracing_subscriber::FmtSubscriber;
@@ -25,26 +38,184 @@ pub struct CmdlineArgs {
     /// URL to a Postgres database
     #[clap(long, env)]
     pub database_url: String,
-    /// URL to a CometBFT node
+    /// URL to a CometBFT node, queried for the native token address
+    /// if it is not given
+    #[clap(long, env, required_unless_present = "native_token")]
+    pub cometbft_url: Option<String>,
+    /// Address of the native token (NAAN); allows running without
+    /// a CometBFT node
     #[clap(long, env)]
-    pub cometbft_url: String,
+    pub native_token: Option<NamadaAddress>,
     /// Time when the Namada chain started
     #[clap(long, env)]
     pub namada_genesis_time: Option<chrono::NaiveDateTime>,
-    /// Node proposing the upgrade from v0 to v1, and v1 to v2
-    #[clap(long, env)]
-    pub upgrade_proposer: NamadaAddress,
-    /// Epoch when the upgrade from v0 to v1 happens
+    /// Node proposing the upgrade from v0 to v1, and v1 to v2; only
+    /// used if no campaign config is given
+    #[clap(long, env, required_unless_present = "campaign_config")]
+    pub upgrade_proposer: Option<NamadaAddress>,
+    /// Epoch when the upgrade from v0 to v1 happens; only used if
+    /// no campaign config is given
+    #[clap(long, env, required_unless_present = "campaign_config")]
+    pub v0_to_v1_upgrade_epoch: Option<NamadaEpoch>,
+    /// Epoch when the upgrade from v1 to v2 happens; only used if
+    /// no campaign config is given
+    #[clap(long, env, required_unless_present = "campaign_config")]
+    pub v1_to_v2_upgrade_epoch: Option<NamadaEpoch>,
+    /// Path to a TOML campaign config, listing the network upgrades
+    /// of the campaign and the proposals voting on them
     #[clap(long, env)]
-    pub v0_to_v1_upgrade_epoch: NamadaEpoch,
-    /// Epoch when the upgrade from v1 to v2 happens
+    pub campaign_config: Option<PathBuf>,
+    /// Path to a TOML or JSON prize schedule, overriding the built-in one
     #[clap(long, env)]
-    pub v1_to_v2_upgrade_epoch: NamadaEpoch,
-    /// Sleep duration between score computations
-    #[clap(long, env, value_parser = parse_dur)]
-    pub sleep_duration: time::Duration,
+    pub prize_schedule: Option<PathBuf>,
+    /// Range of blocks over which pilot uptime is computed
+    #[clap(long, env, value_enum, default_value_t)]
+    pub uptime_window: UptimeWindow,
+    /// Strategy used to recompute the scores of players
+    #[clap(long, env, value_enum, default_value_t)]
+    pub recompute_strategy: RecomputeStrategy,
     #[command(flatten)]
     pub verbosity: Verbosity<InfoLevel>,
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(clap::Subcommand)]
+pub enum Command {
+    /// Continuously process new tasks, and recompute scores and rankings
+    Run(RunArgs),
+    /// Process all pending tasks, recompute scores and rankings, then exit
+    ProcessOnce,
+    /// Recompute the scores of all players, then exit
+    RecomputeScores {
+        /// Print how scores and rankings would change, without
+        /// writing them to the database
+        #[clap(long)]
+        dry_run: bool,
+        /// Output format of the dry run's diff
+        #[clap(long, value_enum, default_value_t)]
+        format: DiffFormat,
+        /// Time the recompute with every strategy and check that they
+        /// agree, without writing scores to the database
+        #[clap(long, conflicts_with = "dry_run")]
+        compare_strategies: bool,
+    },
+    /// Recompute the rankings of all players, then exit
+    RecomputeRankings,
+    /// Process all tasks again starting from the given block height,
+    /// recompute scores and rankings, then exit
+    Reprocess {
+        /// Height of the first block to process again
+        #[clap(long)]
+        from_height: i32,
+    },
+    /// Print the breakdown of the score of a player as JSON, then exit
+    Explain {
+        /// Id of the player whose score is explained
+        #[clap(long)]
+        player: String,
+    },
+    /// Print why transactions did or did not complete a task as JSON,
+    /// then exit
+    ExplainTasks {
+        /// Hash of the transaction, or of the inner transaction
+        /// of a wrapper
+        #[clap(long, required_unless_present = "player", conflicts_with = "player")]
+        tx: Option<String>,
+        /// Id of the player whose transactions are explained
+        #[clap(long)]
+        player: Option<String>,
+    },
+    /// Print a stored transaction as JSON, along with its decoded data
+    /// and the tasks it credited, then exit
+    InspectTx {
+        /// Hash of the transaction, or of the inner transaction
+        /// of a wrapper
+        hash: String,
+    },
+    /// Print statistics of the memos of stored transactions, including
+    /// the memos that failed to parse, as JSON, then exit
+    MemoStats,
+    /// Revoke the tasks credited by failed transactions, recompute
+    /// scores and rankings, then exit
+    RevokeFailedTxTasks,
+    /// Record the block and transaction that completed the tasks
+    /// credited before completions were stored, then exit
+    BackfillTaskCompletions,
+    /// Export the token allocations derived from the current scores
+    /// as a genesis `balances.toml` and a CSV summary, then exit
+    ExportDistribution(ExportDistributionArgs),
+}
+
+impl Command {
+    /// Whether this one-shot command writes to the database, and must
+    /// therefore hold the leader lock while it runs.
+    fn updates_database(&self) -> bool {
+        match self {
+            // NB: the daemon acquires the lock on its own, and
+            // may stand by until it gets it
+            Command::Run(_) => false,
+            Command::ProcessOnce
+            | Command::RecomputeRankings
+            | Command::Reprocess { .. }
+            | Command::RevokeFailedTxTasks
+            | Command::BackfillTaskCompletions => true,
+            Command::RecomputeScores {
+                dry_run,
+                compare_strategies,
+                ..
+            } => !dry_run && !compare_strategies,
+            Command::Explain { .. }
+            | Command::ExplainTasks { .. }
+            | Command::InspectTx { .. }
+            | Command::MemoStats
+            | Command::ExportDistribution(_) => false,
+        }
+    }
+}
+
+#[derive(clap::Args)]
+pub struct ExportDistributionArgs {
+    /// Total amount of tokens to distribute, in the token's base units
+    #[clap(long)]
+    pub total_pool: u128,
+    /// Minimum score a player must have to receive tokens
+    #[clap(long, default_value_t = 1)]
+    pub min_score: i64,
+    /// How to handle the units left over after rounding down allocations
+    #[clap(long, value_enum, default_value_t)]
+    pub remainder_policy: RemainderPolicy,
+    /// Alias of the distributed token in the genesis files
+    #[clap(long, default_value = "NAM")]
+    pub token_alias: String,
+    /// Number of decimal places of the distributed token
+    #[clap(long, default_value_t = 6)]
+    pub denomination: u8,
+    /// Directory to write `balances.toml` and `distribution.csv` to
+    #[clap(long)]
+    pub output_dir: PathBuf,
+}
+
+#[derive(Copy, Clone, Default, clap::ValueEnum)]
+pub enum DiffFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+#[derive(clap::Args)]
+pub struct RunArgs {
+    /// Maximum sleep duration between score computations; scores are
+    /// also recomputed as soon as the crawler indexes new blocks, if
+    /// the database URL sets `sslmode=disable`
+    #[clap(long, env, value_parser = parse_dur)]
+    pub sleep_duration: time::Duration,
+    /// Address to serve the HTTP API on; the API is disabled if unset
+    #[clap(long, env)]
+    pub api_listen_addr: Option<SocketAddr>,
+    /// Address to serve Prometheus metrics on; metrics are not served if unset
+    #[clap(long, env)]
+    pub metrics_listen_addr: Option<SocketAddr>,
 }
 
 const VERSION_STRING: &str = env!("VERGEN_GIT_SHA");
@@ -54,12 +225,17 @@ async fn main() -> anyhow::Result<()> {
     let CmdlineArgs {
         database_url,
         cometbft_url,
+        native_token,
         namada_genesis_time,
         upgrade_proposer,
         v0_to_v1_upgrade_epoch: v0_to_v1,
         v1_to_v2_upgrade_epoch: v1_to_v2,
         verbosity,
-        sleep_duration,
+        campaign_config,
+        prize_schedule,
+        uptime_window,
+        recompute_strategy,
+        command,
     } = CmdlineArgs::parse();
 
     let log_level = match verbosity.log_level_filter() {
@@ -78,15 +254,125 @@ async fn main() -> anyhow::Result<()> {
 
     tracing::info!(version = %VERSION_STRING, "Starting score extractor");
 
+    let prize_schedule_source = prize_schedule
+        .as_ref()
+        .map_or_else(|| "built-in".to_owned(), |path| path.display().to_string());
+    let prize_schedule = PrizeSchedule::load(prize_schedule.as_deref())?;
+    tracing::info!(
+        source = prize_schedule_source,
+        checksum = prize_schedule.checksum(),
+        "Loaded prize schedule"
+    );
+
+    let campaign = match (&campaign_config, &upgrade_proposer, v0_to_v1, v1_to_v2) {
+        (Some(path), ..) => CampaignConfig::load(path)?,
+        (None, Some(upgrade_proposer), Some(v0_to_v1), Some(v1_to_v2)) => {
+            CampaignConfig::legacy(upgrade_proposer, v0_to_v1, v1_to_v2)
+        }
+        _ => unreachable!(
+            "Clap requires an upgrade proposer and upgrade epochs without a campaign config"
+        ),
+    };
+    tracing::info!(
+        source = campaign_config
+            .as_ref()
+            .map_or_else(|| "legacy".to_owned(), |path| path.display().to_string()),
+        checksum = campaign.checksum(),
+        "Loaded campaign config"
+    );
+
+    let native_token = match (native_token, cometbft_url) {
+        (Some(naan), _) => NativeToken::Address(naan),
+        (None, Some(cometbft_url)) => NativeToken::QueryCometBft(CometBftUrl(cometbft_url)),
+        (None, None) => unreachable!("Clap requires a CometBFT URL without a native token address"),
+    };
+
     let context = Context::new(
-        Epochs { v0_to_v1, v1_to_v2 },
-        UpgradeProposer(upgrade_proposer),
         GenesisTime(namada_genesis_time),
-        DatabaseUrl(database_url),
-        CometBftUrl(cometbft_url),
+        DatabaseUrl(database_url.clone()),
+        native_token,
+        prize_schedule,
+        campaign,
+        uptime_window,
+        recompute_strategy,
     )
     .await?;
 
+    // NB: keep the lock until the command is done
+    let _leader_lock = if command.updates_database() {
+        Some(lead_once(&context, database_url.clone())?)
+    } else {
+        None
+    };
+
+    match command {
+        Command::Run(args) => run(&context, database_url, args).await,
+        Command::ProcessOnce => {
+            process_pending_tasks(&context).await?;
+            update_standings(&context).await
+        }
+        Command::RecomputeScores {
+            compare_strategies: true,
+            ..
+        } => compare_recompute_strategies(&context).await,
+        Command::RecomputeScores { dry_run: false, .. } => update_scores(&context).await,
+        Command::RecomputeScores {
+            dry_run: true,
+            format,
+            ..
+        } => dry_run_update_scores(&context, format).await,
+        Command::RecomputeRankings => update_rankings(&context).await,
+        Command::Reprocess { from_height } => {
+            reset_last_processed_tasks_block(&context, from_height).await?;
+            process_pending_tasks(&context).await?;
+            update_standings(&context).await
+        }
+        Command::Explain { player } => explain_player_score(&context, player).await,
+        Command::ExplainTasks { tx, player } => explain_task_decisions(&context, tx, player).await,
+        Command::InspectTx { hash } => inspect_transaction(&context, hash).await,
+        Command::MemoStats => print_memo_stats(&context).await,
+        Command::RevokeFailedTxTasks => {
+            revoke_tasks_from_failed_txs(&context).await?;
+            update_standings(&context).await
+        }
+        Command::BackfillTaskCompletions => backfill_task_completions(&context).await,
+        Command::ExportDistribution(args) => export_distribution(&context, args).await,
+    }
+}
+
+async fn run(
+    context: &Context,
+    database_url: String,
+    RunArgs {
+        sleep_duration,
+        api_listen_addr,
+        metrics_listen_addr,
+    }: RunArgs,
+) -> anyhow::Result<()> {
+    if let Some(addr) = api_listen_addr {
+        let api_cx = context.clone();
+        tokio::spawn(async move {
+            if let Err(err) = api::serve(addr, api_cx).await {
+                tracing::error!(reason = ?err, "HTTP API exited");
+            }
+        });
+    }
+
+    if let Some(addr) = metrics_listen_addr {
+        tokio::spawn(async move {
+            if let Err(err) = metrics::serve(addr).await {
+                tracing::error!(reason = ?err, "Metrics server exited");
+            }
+        });
+    }
+
+    let mut leader_lock = LeaderLock::new(database_url.clone());
+    let new_blocks = Arc::new(Notify::new());
+    tokio::spawn(notifications::listen_for_new_blocks(
+        database_url,
+        Arc::clone(&new_blocks),
+    ));
+
     let mut interval = {
         let mut ticker = time::interval(sleep_duration);
         ticker.tick().await; // skip first tick
@@ -94,32 +380,121 @@ async fn main() -> anyhow::Result<()> {
     };
     let mut ctrl_c = ctrl_c_receiver();
 
-    update_database(&context).await;
+    // NB: whether the last update cycle of this replica completed
+    // every update, such that new blocks alone warrant another one
+    let mut updated = is_leader(&mut leader_lock, context) && update_database(context).await;
     loop {
         tokio::select! {
             _ = &mut ctrl_c => {
                 tracing::info!("Interrupt signal received, exiting");
                 break Ok(());
             }
+            _ = new_blocks.notified() => {
+                updated = is_leader(&mut leader_lock, context)
+                    && update_database_if_behind(context, updated).await;
+                // NB: postpone the fallback timer, since we just
+                // processed all new blocks
+                interval.reset();
+            }
             _ = sleep(sleep_duration, &mut interval) => {
-                update_database(&context).await;
+                updated = is_leader(&mut leader_lock, context)
+                    && update_database_if_behind(context, updated).await;
             }
         }
     }
 }
 
-async fn update_database(context: &Context) {
+/// Check if this replica should update the database, as opposed to
+/// standing by while another one does.
+fn is_leader(leader_lock: &mut LeaderLock, context: &Context) -> bool {
+    let leading = tokio::task::block_in_place(|| leader_lock.try_lead());
+    context.set_leader_backend_pid(leader_lock.backend_pid());
+    match leading {
+        Ok(leading) => leading,
+        Err(err) => {
+            tracing::error!(
+                reason = ?err,
+                replica = leader_lock.replica_name(),
+                "Failed to acquire leader lock, skipping database updates"
+            );
+            false
+        }
+    }
+}
+
+/// Acquire the leader lock for a one-shot command, refusing to run
+/// while another replica holds it.
+fn lead_once(context: &Context, database_url: String) -> anyhow::Result<LeaderLock> {
+    let mut leader_lock = LeaderLock::new(database_url);
+    let leading = tokio::task::block_in_place(|| leader_lock.try_lead())
+        .context("Failed to acquire leader lock")?;
+    if !leading {
+        anyhow::bail!(
+            "Another replica holds the leader lock, refusing to update the database; stop it \
+             before running this command"
+        );
+    }
+    context.set_leader_backend_pid(leader_lock.backend_pid());
+    Ok(leader_lock)
+}
+
+/// Update the database, unless the crawler has not indexed any
+/// blocks since the last update, and that update fully succeeded.
+/// Return whether the last update that ran fully succeeded.
+async fn update_database_if_behind(context: &Context, updated: bool) -> bool {
+    if !updated {
+        tracing::info!("Last database update did not complete, retrying");
+        return update_database(context).await;
+    }
+
+    let has_unprocessed_blocks = context
+        .db_connection_pool()
+        .with(|conn| {
+            conn.build_transaction()
+                .read_only()
+                .run(last_state::has_unprocessed_blocks)
+        })
+        .await;
+    match has_unprocessed_blocks {
+        Ok(Ok(false)) => {
+            tracing::debug!("No new blocks to process, skipping database updates");
+            true
+        }
+        Ok(Ok(true)) => update_database(context).await,
+        Ok(Err(err)) | Err(err) => {
+            tracing::error!(reason = ?err, "Failed to check for new blocks to process");
+            // NB: the database was left as the last update wrote it
+            true
+        }
+    }
+}
+
+/// Run every database update, and return whether all of them
+/// succeeded.
+async fn update_database(context: &Context) -> bool {
     tracing::info!("Checking for new database updates");
+    let mut updated = true;
     if let Err(err) = update_player_tasks(context).await {
         tracing::error!(reason = ?err, "Failed to update player tasks");
+        updated = false;
+    }
+    if let Err(err) = update_task_processing_lag(context).await {
+        tracing::error!(reason = ?err, "Failed to update task processing lag");
     }
     if let Err(err) = update_scores(context).await {
         tracing::error!(reason = ?err, "Failed to update player scores");
+        updated = false;
     }
     if let Err(err) = update_rankings(context).await {
         tracing::error!(reason = ?err, "Failed to update player rankings");
+        updated = false;
     }
-    tracing::info!("All database updates concluded");
+    if let Err(err) = snapshot_scores(context).await {
+        tracing::error!(reason = ?err, "Failed to snapshot player scores");
+        updated = false;
+    }
+    tracing::info!(updated, "All database updates concluded");
+    updated
 }
 
 async fn sleep(dur: time::Duration, interval: &mut time::Interval) {
@@ -140,18 +515,133 @@ fn ctrl_c_receiver() -> oneshot::Receiver<()> {
 
 async fn update_scores(context: &Context) -> anyhow::Result<()> {
     tracing::info!("Recomputing scores in the database");
+    let _timer = metrics::RECOMPUTE_TASK_SCORES_DURATION.start_timer();
     let cloned_cx = context.clone();
     context
         .db_connection_pool()
         .with(|conn| {
-            conn.build_transaction()
-                .read_write()
-                .run(|conn| scores::recompute_task_scores(conn, cloned_cx))
+            conn.build_transaction().read_write().run(|conn| {
+                leader::ensure_leader(conn, cloned_cx.leader_backend_pid())?;
+                scores::recompute_task_scores(conn, cloned_cx)
+            })
         })
         .await??;
     Ok(())
 }
 
+async fn compare_recompute_strategies(context: &Context) -> anyhow::Result<()> {
+    tracing::info!("Comparing score recompute strategies");
+    let cloned_cx = context.clone();
+    let comparison = context
+        .db_connection_pool()
+        .with(|conn| scores::compare_recompute_strategies(conn, cloned_cx))
+        .await??;
+
+    tracing::info!(
+        full_reset = ?comparison.full_reset,
+        incremental = ?comparison.incremental,
+        "Timed score recompute strategies"
+    );
+
+    if comparison.mismatched_players.is_empty() {
+        tracing::info!("Both score recompute strategies agree");
+        Ok(())
+    } else {
+        tracing::error!(
+            no_of_players = comparison.mismatched_players.len(),
+            mismatched_players = ?comparison.mismatched_players,
+            "Score recompute strategies disagree"
+        );
+        Err(anyhow::anyhow!(
+            "Score recompute strategies disagree on {} players",
+            comparison.mismatched_players.len()
+        ))
+    }
+}
+
+async fn dry_run_update_scores(context: &Context, format: DiffFormat) -> anyhow::Result<()> {
+    tracing::info!("Performing a dry run of the score recompute");
+    let cloned_cx = context.clone();
+    let diff = context
+        .db_connection_pool()
+        .with(|conn| scores::dry_run_recompute_task_scores(conn, cloned_cx))
+        .await??;
+
+    match format {
+        DiffFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&diff).context("Failed to serialize score diff")?
+        ),
+        DiffFormat::Csv => {
+            println!("player_id,kind,old_score,new_score,old_ranking,new_ranking,ranking_delta");
+            for player_diff in diff {
+                let fmt_opt = |value: Option<i32>| value.map(|v| v.to_string()).unwrap_or_default();
+                println!(
+                    "{},{},{},{},{},{},{}",
+                    csv::escape(&player_diff.player_id),
+                    player_diff.kind,
+                    player_diff.old_score,
+                    player_diff.new_score,
+                    fmt_opt(player_diff.old_ranking),
+                    fmt_opt(player_diff.new_ranking),
+                    fmt_opt(player_diff.ranking_delta),
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn export_distribution(
+    context: &Context,
+    ExportDistributionArgs {
+        total_pool,
+        min_score,
+        remainder_policy,
+        token_alias,
+        denomination,
+        output_dir,
+    }: ExportDistributionArgs,
+) -> anyhow::Result<()> {
+    let params = DistributionParams {
+        total_pool,
+        min_score,
+        remainder_policy,
+        token_alias,
+        denomination,
+    };
+
+    let distribution = {
+        let params = params.clone();
+        context
+            .db_connection_pool()
+            .with(move |conn| {
+                conn.build_transaction()
+                    .read_only()
+                    .run(|conn| distribution::compute_distribution(conn, &params))
+            })
+            .await??
+    };
+
+    std::fs::create_dir_all(&output_dir)
+        .with_context(|| format!("Failed to create {}", output_dir.display()))?;
+
+    for (file_name, contents) in [
+        ("balances.toml", distribution.to_balances_toml(&params)?),
+        ("distribution.csv", distribution.to_csv(&params)),
+    ] {
+        let path = output_dir.join(file_name);
+        std::fs::write(&path, &contents)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        let checksum = distribution::checksum(&contents);
+        tracing::info!(path = %path.display(), checksum, "Exported distribution file");
+        println!("{checksum}  {}", path.display());
+    }
+
+    Ok(())
+}
+
 async fn update_player_tasks(context: &Context) -> anyhow::Result<()> {
     tracing::info!("Attempting to process new tasks");
     process_new_tasks(context)
@@ -160,9 +650,9 @@ async fn update_player_tasks(context: &Context) -> anyhow::Result<()> {
     Ok(())
 }
 
-async fn process_new_tasks(context: &Context) -> anyhow::Result<()> {
+async fn process_new_tasks(context: &Context) -> anyhow::Result<Option<i32>> {
     let cloned_cx = context.clone();
-    context
+    let (new_block_height, pending_metrics) = context
         .db_connection_pool()
         .with(move |conn| {
             conn.build_transaction()
@@ -170,16 +660,28 @@ async fn process_new_tasks(context: &Context) -> anyhow::Result<()> {
                 .run(|transaction_conn| {
                     let cx = cloned_cx;
 
-                    let new_block_height = process_new_transactions(transaction_conn, &cx)
-                        .map_err(|err| {
+                    leader::ensure_leader(transaction_conn, cx.leader_backend_pid()).map_err(
+                        |err| {
                             tracing::error!(?err, "Database error");
                             DieselErr::RollbackTransaction
-                        })?;
+                        },
+                    )?;
 
-                    process_non_tx_tasks(transaction_conn, &cx).map_err(|err| {
-                        tracing::error!(?err, "Database error");
-                        DieselErr::RollbackTransaction
-                    })?;
+                    let mut pending_metrics = PendingMetrics::default();
+
+                    let new_block_height =
+                        process_new_transactions(transaction_conn, &cx, &mut pending_metrics)
+                            .map_err(|err| {
+                                tracing::error!(?err, "Database error");
+                                DieselErr::RollbackTransaction
+                            })?;
+
+                    process_non_tx_tasks(transaction_conn, &cx, &mut pending_metrics).map_err(
+                        |err| {
+                            tracing::error!(?err, "Database error");
+                            DieselErr::RollbackTransaction
+                        },
+                    )?;
 
                     if let Some(block) = new_block_height {
                         last_state::update_last_processed_tasks_block(transaction_conn, block)
@@ -188,37 +690,215 @@ async fn process_new_tasks(context: &Context) -> anyhow::Result<()> {
                                 DieselErr::RollbackTransaction
                             })?;
                     }
-                    Ok::<_, DieselErr>(())
+                    Ok::<_, DieselErr>((new_block_height, pending_metrics))
                 })
         })
         .await??;
+    pending_metrics.publish();
+
+    Ok(new_block_height)
+}
 
+async fn process_pending_tasks(context: &Context) -> anyhow::Result<()> {
+    tracing::info!("Processing all pending tasks");
+    while let Some(block) = process_new_tasks(context)
+        .await
+        .context("Failed to process new tasks")?
+    {
+        tracing::info!(block, "Processed tasks up to block");
+    }
+    Ok(())
+}
+
+async fn reset_last_processed_tasks_block(
+    context: &Context,
+    from_height: i32,
+) -> anyhow::Result<()> {
+    tracing::info!(from_height, "Resetting last processed tasks block");
+    let leader_pid = context.leader_backend_pid();
+    context
+        .db_connection_pool()
+        .with(move |conn| {
+            conn.build_transaction().read_write().run(|conn| {
+                leader::ensure_leader(conn, leader_pid)?;
+                last_state::update_last_processed_tasks_block(conn, (from_height - 1).max(0))
+            })
+        })
+        .await??;
+    Ok(())
+}
+
+async fn explain_player_score(context: &Context, player_id: String) -> anyhow::Result<()> {
+    let explanation = context
+        .db_connection_pool()
+        .with(move |conn| {
+            conn.build_transaction()
+                .read_only()
+                .run(|conn| scores::explain_player_score(conn, &player_id))
+        })
+        .await??;
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&explanation)
+            .context("Failed to serialize score explanation")?
+    );
+    Ok(())
+}
+
+async fn explain_task_decisions(
+    context: &Context,
+    tx: Option<String>,
+    player: Option<String>,
+) -> anyhow::Result<()> {
+    let cloned_cx = context.clone();
+    let decisions = context
+        .db_connection_pool()
+        .with(move |conn| {
+            conn.build_transaction()
+                .read_only()
+                .run(|conn| match (tx, player) {
+                    (Some(hash), _) => tasks::explain_tx_task_decisions(conn, &cloned_cx, &hash),
+                    (None, Some(player_id)) => tasks::explain_player_task_decisions(
+                        conn,
+                        &cloned_cx,
+                        &player_id,
+                        0,
+                        i64::MAX,
+                    ),
+                    (None, None) => unreachable!("Clap requires a tx hash or a player id"),
+                })
+        })
+        .await??;
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&decisions).context("Failed to serialize task decisions")?
+    );
+    Ok(())
+}
+
+async fn inspect_transaction(context: &Context, hash: String) -> anyhow::Result<()> {
+    let cloned_hash = hash.clone();
+    let inspected_txs = context
+        .db_connection_pool()
+        .with(move |conn| {
+            conn.build_transaction()
+                .read_only()
+                .run(|conn| transactions::inspect_transaction(conn, &cloned_hash))
+        })
+        .await??;
+    if inspected_txs.is_empty() {
+        anyhow::bail!("Transaction {hash} does not exist");
+    }
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&inspected_txs)
+            .context("Failed to serialize inspected transactions")?
+    );
+    Ok(())
+}
+
+async fn print_memo_stats(context: &Context) -> anyhow::Result<()> {
+    let cloned_cx = context.clone();
+    let memo_stats = context
+        .db_connection_pool()
+        .with(move |conn| {
+            conn.build_transaction()
+                .read_only()
+                .run(|conn| memos::compute_memo_stats(conn, cloned_cx.memo_parser()))
+        })
+        .await??;
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&memo_stats).context("Failed to serialize memo statistics")?
+    );
+    Ok(())
+}
+
+async fn revoke_tasks_from_failed_txs(context: &Context) -> anyhow::Result<()> {
+    tracing::info!("Re-evaluating tasks credited by transactions");
+    let cloned_cx = context.clone();
+    context
+        .db_connection_pool()
+        .with(move |conn| {
+            conn.build_transaction().read_write().run(|conn| {
+                leader::ensure_leader(conn, cloned_cx.leader_backend_pid())?;
+                tasks::revoke_tasks_from_failed_txs(conn, &cloned_cx)
+            })
+        })
+        .await?
+        .context("Failed to revoke tasks credited by failed transactions")?;
+    Ok(())
+}
+
+async fn backfill_task_completions(context: &Context) -> anyhow::Result<()> {
+    tracing::info!("Backfilling task completions");
+    let cloned_cx = context.clone();
+    context
+        .db_connection_pool()
+        .with(move |conn| {
+            conn.build_transaction().read_write().run(|conn| {
+                leader::ensure_leader(conn, cloned_cx.leader_backend_pid())?;
+                tasks::backfill_task_completions(conn, &cloned_cx)
+            })
+        })
+        .await?
+        .context("Failed to backfill task completions")?;
     Ok(())
 }
 
 fn process_new_transactions(
     conn: &mut db::Connection,
     cx: &Context,
+    pending_metrics: &mut PendingMetrics,
 ) -> anyhow::Result<Option<i32>> {
     tracing::info!("Processing new transactions");
 
-    transactions::process_last_transactions::<_, PlayerId>(conn, |process_conn, transaction| {
-        tasks::update_task_statuses(
-            process_conn,
-            tasks::TaskInput::Transaction {
-                tx: transaction,
-                cx,
-            },
-        )
-    })
+    transactions::process_last_transactions(
+        conn,
+        cx.memo_parser(),
+        pending_metrics,
+        |process_conn, pending_metrics, transaction| {
+            tasks::update_task_statuses(
+                process_conn,
+                tasks::TaskInput::Transaction {
+                    tx: transaction,
+                    cx,
+                    pending_metrics,
+                },
+            )
+        },
+    )
     .context("Failed to process last transactions")
 }
 
-fn process_non_tx_tasks(conn: &mut db::Connection, cx: &Context) -> anyhow::Result<()> {
+async fn update_task_processing_lag(context: &Context) -> anyhow::Result<()> {
+    let lag = context
+        .db_connection_pool()
+        .with(|conn| {
+            let our_height = last_state::read_last_processed_tasks_block(conn)?.unwrap_or(0);
+            let crawler_height = last_state::read_last_crawled_block(conn)?.unwrap_or(0);
+            anyhow::Ok(crawler_height - our_height)
+        })
+        .await??;
+    metrics::TASK_PROCESSING_LAG.set(lag as i64);
+    Ok(())
+}
+
+fn process_non_tx_tasks(
+    conn: &mut db::Connection,
+    cx: &Context,
+    pending_metrics: &mut PendingMetrics,
+) -> anyhow::Result<()> {
     tracing::info!("Processing non-transaction tasks");
 
-    tasks::update_task_statuses(conn, tasks::TaskInput::Pilot { cx })
-        .context("Failed to process pilot tasks")?;
+    tasks::update_task_statuses(
+        conn,
+        tasks::TaskInput::Pilot {
+            cx,
+            pending_metrics,
+        },
+    )
+    .context("Failed to process pilot tasks")?;
 
     tasks::update_task_statuses(conn, tasks::TaskInput::SpecialTasks)
         .context("Failed to process special tasks")?;
@@ -230,14 +910,39 @@ fn parse_dur(dur: &str) -> anyhow::Result<time::Duration> {
     duration_str::parse_std(dur).context("Failed to parse duration string")
 }
 
+/// Update the scores and rankings of players after their tasks
+/// changed, and snapshot them if a new epoch started.
+async fn update_standings(context: &Context) -> anyhow::Result<()> {
+    update_scores(context).await?;
+    update_rankings(context).await?;
+    snapshot_scores(context).await
+}
+
 async fn update_rankings(context: &Context) -> anyhow::Result<()> {
     tracing::info!("Recomputing player rankings in the database");
+    let _timer = metrics::UPDATE_RANKINGS_DURATION.start_timer();
+    let leader_pid = context.leader_backend_pid();
     context
         .db_connection_pool()
-        .with(|conn| {
-            conn.build_transaction()
-                .read_write()
-                .run(players::update_rankings)
+        .with(move |conn| {
+            conn.build_transaction().read_write().run(|conn| {
+                leader::ensure_leader(conn, leader_pid)?;
+                players::update_rankings(conn)
+            })
+        })
+        .await??;
+    Ok(())
+}
+
+async fn snapshot_scores(context: &Context) -> anyhow::Result<()> {
+    let leader_pid = context.leader_backend_pid();
+    context
+        .db_connection_pool()
+        .with(move |conn| {
+            conn.build_transaction().read_write().run(|conn| {
+                leader::ensure_leader(conn, leader_pid)?;
+                snapshots::snapshot_scores_on_new_epoch(conn)
+            })
         })
         .await??;
     Ok(())
@@ -0,0 +1,138 @@
+use std::collections::BTreeMap;
+
+use anyhow::Context;
+use serde::Serialize;
+use shared::error::MemoError;
+use shared::memo::{MemoParser, MemoPlayer};
+use shared::orm::schema;
+use shared::orm::transaction::TransactionKindDb;
+use shared::transaction::{RawMemo, Transaction};
+
+use crate::db;
+use crate::metrics::PendingMetrics;
+use crate::players::{self, PlayerId};
+
+/// Maximum number of failed memos kept as samples, per failure reason.
+const MAX_FAILED_MEMO_SAMPLES: usize = 5;
+
+/// Player resolved from a memo.
+#[derive(Debug, Clone)]
+pub struct ResolvedMemo {
+    /// Name of the format the memo was written in.
+    pub format: &'static str,
+    pub player_id: PlayerId,
+}
+
+/// Statistics of the memos of stored transactions.
+#[derive(Debug, Default, Serialize)]
+pub struct MemoStats {
+    pub total: u64,
+    /// Number of memos resolved to a player id, per format.
+    pub resolved: BTreeMap<&'static str, u64>,
+    /// Memos that failed to parse, per reason.
+    pub failed: BTreeMap<&'static str, FailedMemos>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct FailedMemos {
+    pub count: u64,
+    /// Some of the memos that failed to parse.
+    pub samples: Vec<String>,
+}
+
+/// Resolve the player referred to by a memo, mapping implicit
+/// addresses back to the players registered with them.
+pub fn resolve_memo(
+    conn: &mut db::Connection,
+    memo_parser: &MemoParser,
+    memo: &RawMemo,
+) -> anyhow::Result<Result<ResolvedMemo, MemoError>> {
+    let parsed = match memo_parser.parse(memo) {
+        Ok(parsed) => parsed,
+        Err(err) => return Ok(Err(err)),
+    };
+    let player_id = match parsed.player {
+        MemoPlayer::PlayerId(player_id) => player_id,
+        MemoPlayer::ImplicitAddress(address) => {
+            match players::player_with_address(conn, &address)? {
+                Some(player_id) => player_id,
+                None => return Ok(Err(MemoError::UnknownAddress { address })),
+            }
+        }
+    };
+    Ok(Ok(ResolvedMemo {
+        format: parsed.format,
+        player_id,
+    }))
+}
+
+/// Resolve the player referred to by the memo of a transaction.
+///
+/// Memos that fail to parse are dropped, and recorded in metrics.
+pub fn resolve_transaction_memo(
+    conn: &mut db::Connection,
+    memo_parser: &MemoParser,
+    pending_metrics: &mut PendingMetrics,
+    transaction: Transaction<RawMemo>,
+) -> anyhow::Result<Transaction<PlayerId>> {
+    let Some(memo) = &transaction.memo else {
+        return Ok(transaction.with_memo(None));
+    };
+    match resolve_memo(conn, memo_parser, memo)? {
+        Ok(ResolvedMemo { format, player_id }) => {
+            pending_metrics.inc_memos_resolved(format);
+            Ok(transaction.with_memo(Some(player_id)))
+        }
+        Err(err) => {
+            tracing::debug!(
+                tx_id = %transaction.hash,
+                reason = %err,
+                "Failed to parse the memo of a transaction"
+            );
+            pending_metrics.inc_memo_parse_failures(err.reason());
+            Ok(transaction.with_memo(None))
+        }
+    }
+}
+
+/// Compute statistics of the memos of all stored transactions,
+/// excluding wrappers, which share the memo of their inner tx.
+pub fn compute_memo_stats(
+    conn: &mut db::Connection,
+    memo_parser: &MemoParser,
+) -> anyhow::Result<MemoStats> {
+    use diesel::prelude::*;
+    use schema::transactions;
+
+    let memos = transactions::table
+        .filter(
+            transactions::dsl::memo
+                .is_not_null()
+                .and(transactions::dsl::kind.ne(TransactionKindDb::Wrapper)),
+        )
+        .select(transactions::dsl::memo.assume_not_null())
+        .load::<Vec<u8>>(conn)
+        .context("Failed to query the memos of transactions")?;
+
+    let mut stats = MemoStats::default();
+
+    for memo in memos.into_iter().map(RawMemo) {
+        stats.total += 1;
+        match resolve_memo(conn, memo_parser, &memo)? {
+            Ok(ResolvedMemo { format, .. }) => {
+                *stats.resolved.entry(format).or_default() += 1;
+            }
+            Err(err) => {
+                let failed = stats.failed.entry(err.reason()).or_default();
+                failed.count += 1;
+                if failed.samples.len() < MAX_FAILED_MEMO_SAMPLES {
+                    failed
+                        .samples
+                        .push(String::from_utf8_lossy(&memo.0).into_owned());
+                }
+            }
+        }
+    }
+
+    Ok(stats)
+}
@@ -0,0 +1,149 @@
+use std::net::SocketAddr;
+
+use anyhow::Context as AnyhowContext;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::Router;
+use either::*;
+use lazy_static::lazy_static;
+use prometheus::{
+    exponential_buckets, register_histogram, register_int_counter_vec, register_int_gauge, Encoder,
+    Histogram, IntCounterVec, IntGauge, TextEncoder,
+};
+use shared::orm::tasks::TaskTypeDb;
+use shared::orm::transaction::TransactionKindDb;
+
+lazy_static! {
+    pub static ref TRANSACTIONS_PER_BATCH: Histogram = register_histogram!(
+        "score_extractor_transactions_per_batch",
+        "Number of transactions processed per batch of blocks",
+        exponential_buckets(1.0, 4.0, 10).unwrap()
+    )
+    .unwrap();
+    pub static ref TASKS_INSERTED: IntCounterVec = register_int_counter_vec!(
+        "score_extractor_tasks_inserted_total",
+        "Number of tasks inserted in the database, per task type",
+        &["task"]
+    )
+    .unwrap();
+    pub static ref UNIDENTIFIED_TASKS_INSERTED: IntCounterVec = register_int_counter_vec!(
+        "score_extractor_unidentified_tasks_inserted_total",
+        "Number of unidentified tasks inserted in the database, per tx kind",
+        &["tx_kind"]
+    )
+    .unwrap();
+    pub static ref MEMOS_RESOLVED: IntCounterVec = register_int_counter_vec!(
+        "score_extractor_memos_resolved_total",
+        "Number of transaction memos resolved to a player, per memo format",
+        &["format"]
+    )
+    .unwrap();
+    pub static ref MEMO_PARSE_FAILURES: IntCounterVec = register_int_counter_vec!(
+        "score_extractor_memo_parse_failures_total",
+        "Number of transaction memos that failed to parse, per reason",
+        &["reason"]
+    )
+    .unwrap();
+    pub static ref RECOMPUTE_TASK_SCORES_DURATION: Histogram = register_histogram!(
+        "score_extractor_recompute_task_scores_duration_seconds",
+        "Time taken to recompute the task scores of all players",
+        exponential_buckets(0.5, 2.0, 12).unwrap()
+    )
+    .unwrap();
+    pub static ref UPDATE_RANKINGS_DURATION: Histogram = register_histogram!(
+        "score_extractor_update_rankings_duration_seconds",
+        "Time taken to update the rankings of all players",
+        exponential_buckets(0.5, 2.0, 12).unwrap()
+    )
+    .unwrap();
+    pub static ref TASK_PROCESSING_LAG: IntGauge = register_int_gauge!(
+        "score_extractor_task_processing_lag_blocks",
+        "Number of crawled blocks whose tasks have yet to be processed"
+    )
+    .unwrap();
+}
+
+/// Metrics recorded from within a database transaction, which are
+/// only published once it commits, such that rolled back updates
+/// are not accounted for.
+#[derive(Debug, Default)]
+pub struct PendingMetrics {
+    transactions_per_batch: Vec<usize>,
+    tasks_inserted: Vec<Either<TransactionKindDb, TaskTypeDb>>,
+    memos_resolved: Vec<&'static str>,
+    memo_parse_failures: Vec<&'static str>,
+}
+
+impl PendingMetrics {
+    /// Record the number of transactions processed in a batch.
+    pub fn observe_transactions_per_batch(&mut self, processed_txs: usize) {
+        self.transactions_per_batch.push(processed_txs);
+    }
+
+    /// Record the insertion of a new task in the database.
+    pub fn inc_tasks_inserted(&mut self, task_type: Either<&TransactionKindDb, &TaskTypeDb>) {
+        self.tasks_inserted
+            .push(task_type.map_either(|tx_kind| *tx_kind, |task| *task));
+    }
+
+    /// Record a memo resolved to a player, with the given format.
+    pub fn inc_memos_resolved(&mut self, format: &'static str) {
+        self.memos_resolved.push(format);
+    }
+
+    /// Record a memo that failed to parse, for the given reason.
+    pub fn inc_memo_parse_failures(&mut self, reason: &'static str) {
+        self.memo_parse_failures.push(reason);
+    }
+
+    /// Publish the recorded metrics. This must only be called once
+    /// the transaction they were recorded from has committed.
+    pub fn publish(self) {
+        for processed_txs in self.transactions_per_batch {
+            TRANSACTIONS_PER_BATCH.observe(processed_txs as f64);
+        }
+        for task_type in self.tasks_inserted {
+            match task_type {
+                Left(tx_kind) => UNIDENTIFIED_TASKS_INSERTED
+                    .with_label_values(&[&format!("{tx_kind:?}")])
+                    .inc(),
+                Right(task) => TASKS_INSERTED
+                    .with_label_values(&[&format!("{task:?}")])
+                    .inc(),
+            }
+        }
+        for format in self.memos_resolved {
+            MEMOS_RESOLVED.with_label_values(&[format]).inc();
+        }
+        for reason in self.memo_parse_failures {
+            MEMO_PARSE_FAILURES.with_label_values(&[reason]).inc();
+        }
+    }
+}
+
+/// Serve the Prometheus metrics of this process on `/metrics`,
+/// until the server fails.
+pub async fn serve(addr: SocketAddr) -> anyhow::Result<()> {
+    let app = Router::new().route("/metrics", get(get_metrics));
+
+    tracing::info!(%addr, "Serving Prometheus metrics");
+
+    axum::Server::bind(&addr)
+        .serve(app.into_make_service())
+        .await
+        .context("Metrics server failed")
+}
+
+async fn get_metrics() -> Result<String, StatusCode> {
+    let mut buffer = vec![];
+    TextEncoder::new()
+        .encode(&prometheus::gather(), &mut buffer)
+        .map_err(|err| {
+            tracing::error!(reason = ?err, "Failed to encode metrics");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    String::from_utf8(buffer).map_err(|err| {
+        tracing::error!(reason = ?err, "Metrics are not valid UTF-8");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
@@ -0,0 +1,112 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context};
+use futures::stream::{self, StreamExt};
+use tokio::sync::Notify;
+use tokio::time;
+use tokio_postgres::config::SslMode;
+use tokio_postgres::{AsyncMessage, Config, NoTls};
+
+/// Channel notified by the database whenever the crawler
+/// indexes new blocks.
+pub const CRAWLER_STATE_CHANNEL: &str = "crawler_state_advanced";
+
+/// Listen for notifications of new blocks indexed by the crawler,
+/// waking up a waiter of `new_blocks` on each of them.
+///
+/// Notifications are received over a dedicated connection, which
+/// is reestablished whenever it fails; notifications sent while
+/// disconnected are lost.
+///
+/// TLS is not supported for this connection. Unless the database URL
+/// sets `sslmode=disable`, no notifications are received, and new
+/// blocks are only picked up by the fallback interval: this includes
+/// the default `prefer` mode, which would otherwise silently fall
+/// back to an unencrypted connection.
+pub async fn listen_for_new_blocks(database_url: String, new_blocks: Arc<Notify>) {
+    const RETRY_SLEEP: time::Duration = time::Duration::from_secs(30);
+
+    let config = match database_url.parse::<Config>() {
+        Ok(config) => config,
+        Err(err) => {
+            tracing::error!(
+                reason = ?err,
+                "Unsupported database URL for notifications, not listening for new blocks; \
+                 falling back to the sleep interval"
+            );
+            return;
+        }
+    };
+    let ssl_mode = config.get_ssl_mode();
+    if !matches!(ssl_mode, SslMode::Disable) {
+        tracing::error!(
+            ?ssl_mode,
+            "Notifications can not be received over TLS, not listening for new blocks; \
+             falling back to the sleep interval. Set sslmode=disable in the database URL \
+             to receive them over an unencrypted connection"
+        );
+        return;
+    }
+
+    loop {
+        let Err(err) = listen(&config, &new_blocks).await else {
+            continue;
+        };
+        tracing::warn!(
+            reason = ?err,
+            retry_sleep = ?RETRY_SLEEP,
+            "Stopped listening for new blocks, retrying"
+        );
+        time::sleep(RETRY_SLEEP).await;
+    }
+}
+
+async fn listen(config: &Config, new_blocks: &Notify) -> anyhow::Result<()> {
+    let (client, mut connection) = config
+        .connect(NoTls)
+        .await
+        .context("Failed to connect to Postgres")?;
+    let mut messages = stream::poll_fn(move |cx| connection.poll_message(cx));
+
+    // NB: the connection must be driven for `LISTEN` to complete
+    let listen_statement = format!("LISTEN {CRAWLER_STATE_CHANNEL}");
+    let listen = client.batch_execute(&listen_statement);
+    tokio::pin!(listen);
+    loop {
+        tokio::select! {
+            result = &mut listen => {
+                result.with_context(|| format!("Failed to listen on {CRAWLER_STATE_CHANNEL}"))?;
+                break;
+            }
+            message = messages.next() => {
+                handle_message(message, new_blocks)?;
+            }
+        }
+    }
+    tracing::info!(channel = CRAWLER_STATE_CHANNEL, "Listening for new blocks");
+
+    loop {
+        handle_message(messages.next().await, new_blocks)?;
+    }
+}
+
+fn handle_message(
+    message: Option<Result<AsyncMessage, tokio_postgres::Error>>,
+    new_blocks: &Notify,
+) -> anyhow::Result<()> {
+    match message {
+        Some(Ok(AsyncMessage::Notification(notification)))
+            if notification.channel() == CRAWLER_STATE_CHANNEL =>
+        {
+            tracing::debug!(
+                height = notification.payload(),
+                "Crawler indexed new blocks"
+            );
+            new_blocks.notify_one();
+        }
+        Some(Ok(_)) => {}
+        Some(Err(err)) => return Err(err).context("Postgres connection failed"),
+        None => return Err(anyhow!("Postgres connection closed")),
+    }
+    Ok(())
+}
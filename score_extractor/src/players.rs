@@ -1,3 +1,5 @@
+use std::ops::RangeInclusive;
+
 use anyhow::{anyhow, Context};
 use shared::orm::players::PlayerKindDb;
 use shared::orm::schema;
@@ -12,9 +14,12 @@ pub const NUMBER_CREW_MEMBERS: usize = 129238;
 
 pub struct PilotValidatorAddress(pub String);
 
+/// Process all pilots who have yet to complete the given task, for
+/// the given upgrade (`NO_UPGRADE` if the task is not upgrade bound).
 pub fn process_all_pilots_with_incomplete_tasks<F>(
     conn: &mut db::Connection,
     task_type: TaskTypeDb,
+    upgrade: &str,
     mut process: F,
 ) -> anyhow::Result<()>
 where
@@ -27,7 +32,11 @@ where
     use schema::tasks;
 
     let players_who_completed_task = tasks::table
-        .filter(tasks::dsl::task.eq(task_type))
+        .filter(
+            tasks::dsl::task
+                .eq(task_type)
+                .and(tasks::dsl::upgrade.eq(upgrade)),
+        )
         .select(tasks::dsl::player_id);
 
     players
@@ -117,23 +126,113 @@ pub fn player_exists(conn: &mut db::Connection, player_id: &str) -> anyhow::Resu
         .with_context(|| format!("Failed to check if player with id {player_id} exists"))
 }
 
-fn reset_rankings(conn: &mut db::Connection) -> anyhow::Result<()> {
+/// Return the range of epochs in which the validator of a pilot
+/// was part of the validator set, if it ever was.
+pub fn pilot_validator_set_epochs(
+    conn: &mut db::Connection,
+    pilot_addr: &PilotValidatorAddress,
+) -> anyhow::Result<Option<RangeInclusive<i32>>> {
+    use diesel::dsl::{max, min};
     use diesel::prelude::*;
-    use schema::player_ranks;
+    use schema::tm_addresses::dsl::*;
 
-    diesel::delete(player_ranks::table)
-        .execute(conn)
-        .context("Failed to delete existing player rankings")?;
+    let PilotValidatorAddress(pilot_addr) = pilot_addr;
+
+    let epochs = tm_addresses
+        .filter(validator_namada_address.eq(pilot_addr))
+        .select((min(epoch), max(epoch)))
+        .first::<(Option<i32>, Option<i32>)>(conn)
+        .with_context(|| format!("Failed to query validator set epochs of {pilot_addr}"))?;
+
+    Ok(match epochs {
+        (Some(first_epoch), Some(last_epoch)) => Some(first_epoch..=last_epoch),
+        _ => None,
+    })
+}
+
+/// Create the temporary table new player rankings are computed into,
+/// before being merged into `player_ranks`. It is dropped at the end
+/// of the current transaction.
+fn create_next_rankings_table(conn: &mut db::Connection) -> anyhow::Result<()> {
+    use diesel::prelude::*;
 
-    // NB: this is necessary to avoid overflowing the id
-    // counter of the `player_ranks` table
+    diesel::sql_query("DROP TABLE IF EXISTS pg_temp.player_ranks_next")
+        .execute(conn)
+        .context("Failed to drop previous player rankings table")?;
     diesel::sql_query(
         r#"
-        ALTER SEQUENCE player_ranks_id_seq RESTART WITH 1
+        CREATE TEMPORARY TABLE player_ranks_next (
+            ranking INT NOT NULL,
+            player_id VARCHAR NOT NULL,
+            previous_ranking INT,
+            ranking_delta INT
+        ) ON COMMIT DROP
         "#,
     )
     .execute(conn)
-    .context("Failed to reset the id counter of player rankings")?;
+    .context("Failed to create the table of new player rankings")?;
+
+    Ok(())
+}
+
+/// Merge the rankings computed into `player_ranks_next` with
+/// `player_ranks`, only touching the rows that changed.
+///
+/// Readers keep seeing the previous rankings until the
+/// transaction commits.
+fn sync_player_rankings(conn: &mut db::Connection) -> anyhow::Result<()> {
+    use diesel::prelude::*;
+
+    let deleted_rows = diesel::sql_query(
+        r#"
+        DELETE FROM player_ranks
+        WHERE NOT EXISTS (
+            SELECT 1 FROM player_ranks_next AS next
+            WHERE next.player_id = player_ranks.player_id
+        )
+        "#,
+    )
+    .execute(conn)
+    .context("Failed to delete stale player rankings")?;
+
+    // NB: existing rows are updated in place, rather than deleted and
+    // inserted again, so as not to exhaust the id counter
+    let updated_rows = diesel::sql_query(
+        r#"
+        UPDATE player_ranks
+        SET ranking = next.ranking,
+            previous_ranking = next.previous_ranking,
+            ranking_delta = next.ranking_delta
+        FROM player_ranks_next AS next
+        WHERE next.player_id = player_ranks.player_id
+          AND (next.ranking, next.previous_ranking, next.ranking_delta)
+              IS DISTINCT FROM
+              (player_ranks.ranking, player_ranks.previous_ranking, player_ranks.ranking_delta)
+        "#,
+    )
+    .execute(conn)
+    .context("Failed to update changed player rankings")?;
+
+    let inserted_rows = diesel::sql_query(
+        r#"
+        INSERT INTO player_ranks ( ranking, player_id, previous_ranking, ranking_delta )
+        SELECT next.ranking, next.player_id, next.previous_ranking, next.ranking_delta
+        FROM player_ranks_next AS next
+        WHERE NOT EXISTS (
+            SELECT 1 FROM player_ranks
+            WHERE player_ranks.player_id = next.player_id
+        )
+        "#,
+    )
+    .execute(conn)
+    .context("Failed to insert new player rankings")?;
+
+    tracing::info!(
+        deleted_rows,
+        updated_rows,
+        inserted_rows,
+        "Updated player rankings"
+    );
 
     Ok(())
 }
@@ -146,14 +245,31 @@ fn set_rankings_for_player_kind(
 
     let affected_rows = diesel::sql_query(
         r#"
-        INSERT INTO player_ranks ( ranking, player_id )
+        INSERT INTO player_ranks_next ( ranking, player_id, previous_ranking, ranking_delta )
         SELECT
-          ROW_NUMBER() OVER (ORDER BY score DESC, internal_id)
-            AS rank_index,
-          id
-            AS player_id
-        FROM players WHERE players.id in
-            (SELECT id FROM players WHERE kind = $1)
+          new_ranks.ranking,
+          new_ranks.player_id,
+          -- NB: keep the last movement of players whose ranking
+          -- did not change, rather than erasing it on the next tick
+          CASE WHEN player_ranks.ranking = new_ranks.ranking
+            THEN player_ranks.previous_ranking
+            ELSE player_ranks.ranking
+          END
+            AS previous_ranking,
+          CASE WHEN player_ranks.ranking = new_ranks.ranking
+            THEN player_ranks.ranking_delta
+            ELSE player_ranks.ranking - new_ranks.ranking
+          END
+            AS ranking_delta
+        FROM (
+          SELECT
+            ROW_NUMBER() OVER (ORDER BY score DESC, internal_id)
+              AS ranking,
+            id
+              AS player_id
+          FROM players WHERE kind = $1
+        ) AS new_ranks
+        LEFT JOIN player_ranks ON player_ranks.player_id = new_ranks.player_id
         "#,
     )
     .bind::<schema::sql_types::PlayerKind, _>(&player_kind)
@@ -163,19 +279,75 @@ fn set_rankings_for_player_kind(
     tracing::info!(
         %player_kind,
         no_of_players = affected_rows,
-        "Updated player rankings in the database"
+        "Computed new player rankings"
     );
 
     Ok(())
 }
 
+/// Check whether the ranking of any player differs from the one
+/// stored in `player_ranks`, including players who joined or left.
+fn rankings_changed(conn: &mut db::Connection) -> anyhow::Result<bool> {
+    use diesel::dsl::sql;
+    use diesel::prelude::*;
+    use diesel::sql_types::Bool;
+
+    diesel::select(sql::<Bool>(
+        r#"
+        EXISTS (
+          SELECT 1
+          FROM (
+            SELECT
+              ROW_NUMBER() OVER (PARTITION BY kind ORDER BY score DESC, internal_id)
+                AS ranking,
+              id
+                AS player_id
+            FROM players
+          ) AS new_ranks
+          FULL OUTER JOIN player_ranks ON player_ranks.player_id = new_ranks.player_id
+          WHERE new_ranks.ranking IS DISTINCT FROM player_ranks.ranking
+        )
+        "#,
+    ))
+    .get_result::<bool>(conn)
+    .context("Failed to compare player rankings")
+}
+
+/// Recompute the rankings of all players, keeping track of
+/// their previous ranking.
+///
+/// Rankings are left untouched if no player moved.
 pub fn update_rankings(conn: &mut db::Connection) -> anyhow::Result<()> {
-    reset_rankings(conn).context("Failed to reset player rankings")?;
+    if !rankings_changed(conn)? {
+        tracing::info!("Player rankings did not change, skipping update");
+        return Ok(());
+    }
+
+    create_next_rankings_table(conn).context("Failed to prepare new player rankings")?;
 
     set_rankings_for_player_kind(conn, PlayerKindDb::Pilot)
         .context("Failed to update pilot rankings")?;
     set_rankings_for_player_kind(conn, PlayerKindDb::Crew)
         .context("Failed to update crew rankings")?;
 
+    sync_player_rankings(conn).context("Failed to publish new player rankings")?;
+
     Ok(())
 }
+
+/// Return the id of the player registered with the given address.
+pub fn player_with_address(
+    conn: &mut db::Connection,
+    address: &str,
+) -> anyhow::Result<Option<PlayerId>> {
+    use diesel::prelude::*;
+    use schema::players::dsl::*;
+
+    players
+        .filter(namada_player_address.eq(address))
+        .select(id)
+        .first(conn)
+        .optional()
+        .map(|player_id| player_id.map(PlayerId))
+        .with_context(|| format!("Failed to query player with address {address}"))
+}
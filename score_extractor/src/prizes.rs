@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Context as AnyhowContext};
+use either::*;
+use namada_core::types::hash::Hash as NamadaHash;
+use serde::Deserialize;
+use shared::orm::players::PlayerKindDb;
+use shared::orm::tasks::TaskTypeDb;
+use shared::orm::transaction::TransactionKindDb;
+
+use crate::tasks::CompletableBy;
+
+/// Version of the prize schedule format understood by this binary.
+pub const PRIZE_SCHEDULE_VERSION: u32 = 1;
+
+/// Prize schedule used when no schedule file is supplied.
+const DEFAULT_PRIZE_SCHEDULE: &str = include_str!("../prizes.toml");
+
+#[derive(Debug, Copy, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Score {
+    Fixed(f64),
+    RelativeToCompletion(f64),
+}
+
+impl Score {
+    pub fn total(&self) -> f64 {
+        match self {
+            Score::Fixed(total) | Score::RelativeToCompletion(total) => *total,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PlayerKindPrizes {
+    pub crew: Option<Score>,
+    pub pilot: Option<Score>,
+}
+
+impl PlayerKindPrizes {
+    pub fn get(&self, player_kind: &PlayerKindDb) -> Option<Score> {
+        match player_kind {
+            PlayerKindDb::Crew => self.crew,
+            PlayerKindDb::Pilot => self.pilot,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawPrizeSchedule {
+    version: u32,
+    #[serde(default)]
+    tasks: HashMap<TaskTypeDb, PlayerKindPrizes>,
+    #[serde(default)]
+    unidentified_tasks: HashMap<TransactionKindDb, PlayerKindPrizes>,
+}
+
+/// Pool prizes of every task, per player kind.
+#[derive(Debug)]
+pub struct PrizeSchedule {
+    tasks: HashMap<TaskTypeDb, PlayerKindPrizes>,
+    unidentified_tasks: HashMap<TransactionKindDb, PlayerKindPrizes>,
+    checksum: String,
+}
+
+impl PrizeSchedule {
+    /// Load the prize schedule at `path`, falling back to the built-in
+    /// schedule if no path is given.
+    ///
+    /// Files ending in `.json` are parsed as JSON, anything else as TOML.
+    pub fn load(path: Option<&Path>) -> anyhow::Result<Self> {
+        let Some(path) = path else {
+            return Self::parse_toml(DEFAULT_PRIZE_SCHEDULE)
+                .context("Failed to parse built-in prize schedule");
+        };
+
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read prize schedule from {}", path.display()))?;
+
+        let is_json = path
+            .extension()
+            .map(|ext| ext.eq_ignore_ascii_case("json"))
+            .unwrap_or(false);
+
+        if is_json {
+            Self::parse_json(&contents)
+        } else {
+            Self::parse_toml(&contents)
+        }
+        .with_context(|| format!("Failed to parse prize schedule at {}", path.display()))
+    }
+
+    pub fn parse_toml(contents: &str) -> anyhow::Result<Self> {
+        let raw = toml::from_str(contents).context("Invalid TOML prize schedule")?;
+        Self::new(raw, contents)
+    }
+
+    pub fn parse_json(contents: &str) -> anyhow::Result<Self> {
+        let raw = serde_json::from_str(contents).context("Invalid JSON prize schedule")?;
+        Self::new(raw, contents)
+    }
+
+    fn new(raw: RawPrizeSchedule, contents: &str) -> anyhow::Result<Self> {
+        if raw.version != PRIZE_SCHEDULE_VERSION {
+            return Err(anyhow!(
+                "Unsupported prize schedule version {}, expected {PRIZE_SCHEDULE_VERSION}",
+                raw.version
+            ));
+        }
+
+        let schedule = Self {
+            tasks: raw.tasks,
+            unidentified_tasks: raw.unidentified_tasks,
+            checksum: NamadaHash::sha256(contents.as_bytes()).to_string(),
+        };
+        schedule.validate()?;
+
+        Ok(schedule)
+    }
+
+    /// Check that every task that can be completed by some player kind
+    /// has a prize for it, and that no prizes are set for player kinds
+    /// which cannot complete a task.
+    fn validate(&self) -> anyhow::Result<()> {
+        for task in TaskTypeDb::ALL.iter() {
+            validate_task_prizes(Right(task), self.tasks.get(task))?;
+        }
+        for tx_kind in TransactionKindDb::ALL.iter() {
+            validate_task_prizes(Left(tx_kind), self.unidentified_tasks.get(tx_kind))?;
+        }
+        Ok(())
+    }
+
+    /// SHA-256 checksum of the schedule's source.
+    pub fn checksum(&self) -> &str {
+        &self.checksum
+    }
+
+    /// Return the pool prize of a task, for the given player kind.
+    pub fn get(
+        &self,
+        player_kind: &PlayerKindDb,
+        task_type: Either<&TransactionKindDb, &TaskTypeDb>,
+    ) -> Option<Score> {
+        task_type
+            .either(
+                |tx_kind| self.unidentified_tasks.get(tx_kind),
+                |task| self.tasks.get(task),
+            )?
+            .get(player_kind)
+    }
+}
+
+fn validate_task_prizes(
+    task_type: Either<&TransactionKindDb, &TaskTypeDb>,
+    prizes: Option<&PlayerKindPrizes>,
+) -> anyhow::Result<()> {
+    let no_prizes = PlayerKindPrizes::default();
+    let prizes = prizes.unwrap_or(&no_prizes);
+    let task_name = task_type.either(
+        |tx_kind| format!("unidentified task {tx_kind:?}"),
+        |task| format!("task {task:?}"),
+    );
+
+    let (completable_by_crew, completable_by_pilots) = match CompletableBy::check(task_type) {
+        CompletableBy::NoOne => (false, false),
+        CompletableBy::OnlyCrew => (true, false),
+        CompletableBy::OnlyPilots => (false, true),
+        CompletableBy::DependsOnPlayerKind => (true, true),
+    };
+
+    for (player_kind, completable, prize) in [
+        (PlayerKindDb::Crew, completable_by_crew, prizes.crew),
+        (PlayerKindDb::Pilot, completable_by_pilots, prizes.pilot),
+    ] {
+        match (completable, prize) {
+            (true, None) => {
+                return Err(anyhow!("Missing {player_kind} prize for {task_name}"));
+            }
+            (false, Some(_)) => {
+                return Err(anyhow!(
+                    "The {task_name} cannot be completed by {player_kind} players, \
+                     but it has a prize assigned to them"
+                ));
+            }
+            (true, Some(score)) if !score.total().is_finite() || score.total() < 0.0 => {
+                return Err(anyhow!(
+                    "Invalid {player_kind} prize {score:?} for {task_name}"
+                ));
+            }
+            _ => (),
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn built_in_prize_schedule_is_valid() {
+        let schedule = PrizeSchedule::load(None).expect("Built-in prize schedule should be valid");
+
+        assert!(schedule
+            .get(&PlayerKindDb::Crew, Right(&TaskTypeDb::ClaimPosRewards))
+            .is_some());
+    }
+
+    #[test]
+    fn rejects_prize_schedule_with_missing_prizes() {
+        let error = PrizeSchedule::parse_toml("version = 1").unwrap_err();
+
+        assert!(format!("{error:#}").contains("Missing"));
+    }
+
+    #[test]
+    fn rejects_unsupported_prize_schedule_version() {
+        let error = PrizeSchedule::parse_toml("version = 0").unwrap_err();
+
+        assert!(format!("{error:#}").contains("Unsupported prize schedule version 0"));
+    }
+}
@@ -1,21 +1,27 @@
 use std::collections::hash_map::{self, HashMap};
 use std::collections::HashSet;
 use std::marker::PhantomData;
+use std::time::{Duration, Instant};
 
-use anyhow::Context as AnyhowContext;
+use anyhow::{anyhow, Context as AnyhowContext};
 use either::*;
+use serde::Serialize;
+use shared::orm::player_task_scores::{PlayerTaskScoreDb, PlayerTaskScoreInsertDb, PoolKindDb};
 use shared::orm::players::PlayerKindDb;
 use shared::orm::schema;
-use shared::orm::tasks::{TaskDb, TaskTypeDb, UnidentifiedTaskDb};
+use shared::orm::tasks::{TaskDb, TaskTypeDb, UnidentifiedTaskDb, NO_UPGRADE};
 use shared::orm::transaction::TransactionKindDb;
 
-use crate::context::Context;
+use crate::context::{Context, UptimeWindow};
 use crate::db;
+use crate::governance::eligible_proposals;
+use crate::last_state;
+use crate::players::{self, NUMBER_CREW_MEMBERS, NUMBER_PILOTS};
 use crate::players::{
-    process_all_pilots, process_all_pilots_with_nonnull_validator_addr, PilotValidatorAddress,
-    PlayerId,
+    pilot_validator_set_epochs, process_all_pilots_with_nonnull_validator_addr,
+    PilotValidatorAddress, PlayerId,
 };
-use crate::players::{NUMBER_CREW_MEMBERS, NUMBER_PILOTS};
+use crate::prizes::Score;
 use crate::tasks::CompletableBy;
 
 #[derive(Debug)]
@@ -52,41 +58,63 @@ impl<P, S> PoolPrize<P, S> {
 }
 
 impl PoolPrize<PlayerKindCrew, FixedShare> {
-    fn update_score(self, conn: &mut db::Connection, player_id: &str) -> anyhow::Result<()> {
+    fn task_share(self, CompletedBy(completed_players): CompletedBy) -> TaskShare {
         let FixedShare(total_shares) = self.total_shares;
-        let share = (total_shares / NUMBER_CREW_MEMBERS as f64) as i64;
-        tracing::info!(player_id, share, "Computed score shares for player");
-        set_assign_player_score(conn, player_id, share)
+        TaskShare::new(
+            PoolKindDb::Fixed,
+            total_shares,
+            NUMBER_CREW_MEMBERS as _,
+            completed_players,
+        )
     }
 }
 
 impl PoolPrize<PlayerKindPilot, FixedShare> {
-    fn update_score(self, conn: &mut db::Connection, player_id: &str) -> anyhow::Result<()> {
+    fn task_share(self, CompletedBy(completed_players): CompletedBy) -> TaskShare {
         let FixedShare(total_shares) = self.total_shares;
-        let share = (total_shares / NUMBER_PILOTS as f64) as i64;
-        tracing::info!(player_id, share, "Computed score shares for player");
-        set_assign_player_score(conn, player_id, share)
+        TaskShare::new(
+            PoolKindDb::Fixed,
+            total_shares,
+            NUMBER_PILOTS as _,
+            completed_players,
+        )
     }
 }
 
 impl<P> PoolPrize<P, RelativeToCompletionShare> {
-    fn update_score(
-        self,
-        conn: &mut db::Connection,
-        player_id: &str,
-        CompletedBy(completed_players): CompletedBy,
-    ) -> anyhow::Result<()> {
+    fn task_share(self, CompletedBy(completed_players): CompletedBy) -> TaskShare {
         let RelativeToCompletionShare(total_shares) = self.total_shares;
-        let share = (total_shares / completed_players as f64) as i64;
-        tracing::info!(player_id, share, "Computed score shares for player");
-        set_assign_player_score(conn, player_id, share)
+        TaskShare::new(
+            PoolKindDb::RelativeToCompletion,
+            total_shares,
+            completed_players,
+            completed_players,
+        )
     }
 }
 
-#[derive(Debug, Copy, Clone)]
-enum Score {
-    Fixed(f64),
-    RelativeToCompletion(f64),
+/// Share of a pool prize assigned to a player.
+#[derive(Debug)]
+struct TaskShare {
+    pool_kind: PoolKindDb,
+    pool_total: f64,
+    /// Number of players who completed the task.
+    completed_by: i64,
+    share: i64,
+}
+
+impl TaskShare {
+    /// Split a pool prize among `split_among` players. Fixed pools are
+    /// split among every player of a kind, regardless of how many of
+    /// them completed the task.
+    fn new(pool_kind: PoolKindDb, pool_total: f64, split_among: i64, completed_by: i64) -> Self {
+        Self {
+            pool_kind,
+            pool_total,
+            completed_by,
+            share: (pool_total / split_among as f64) as i64,
+        }
+    }
 }
 
 #[derive(Copy, Clone)]
@@ -99,6 +127,18 @@ enum PoolPrizeKind {
     RelativePilot(PoolPrize<PlayerKindPilot, RelativeToCompletionShare>),
 }
 
+/// How the scores of players are recomputed from their tasks.
+#[derive(Debug, Copy, Clone, Default, clap::ValueEnum)]
+pub enum RecomputeStrategy {
+    /// Aggregate the task shares of all players with set-based queries,
+    /// and only rewrite the players whose score changed.
+    #[default]
+    Incremental,
+    /// Reset the score of every player, then add each of their task
+    /// shares to it, one update at a time.
+    FullReset,
+}
+
 pub fn fetch_pilots_with_nonzero_score(
     conn: &mut db::Connection,
 ) -> anyhow::Result<HashSet<String>> {
@@ -117,15 +157,293 @@ pub fn fetch_pilots_with_nonzero_score(
     Ok(set)
 }
 
+/// Breakdown of the score of a player, per task.
+#[derive(Debug, Serialize)]
+pub struct PlayerScoreExplanation {
+    pub player_id: String,
+    pub kind: PlayerKindDb,
+    pub score: i64,
+    pub sum_of_task_shares: i64,
+    pub tasks: Vec<PlayerTaskScoreDb>,
+}
+
+pub fn explain_player_score(
+    conn: &mut db::Connection,
+    player_id: &str,
+) -> anyhow::Result<PlayerScoreExplanation> {
+    use diesel::prelude::*;
+    use schema::player_task_scores;
+    use schema::players;
+
+    let (kind, score) = players::table
+        .filter(players::dsl::id.eq(player_id))
+        .select((players::dsl::kind, players::dsl::score))
+        .first::<(PlayerKindDb, i64)>(conn)
+        .optional()
+        .with_context(|| format!("Failed to query player {player_id}"))?
+        .ok_or_else(|| anyhow!("Player {player_id} does not exist"))?;
+
+    let tasks = player_task_scores::table
+        .filter(player_task_scores::dsl::player_id.eq(player_id))
+        .order(player_task_scores::dsl::id)
+        .select(PlayerTaskScoreDb::as_select())
+        .load(conn)
+        .with_context(|| format!("Failed to query task scores of player {player_id}"))?;
+
+    Ok(PlayerScoreExplanation {
+        player_id: player_id.to_owned(),
+        kind,
+        score,
+        sum_of_task_shares: tasks.iter().map(|task| task.share).sum(),
+        tasks,
+    })
+}
+
+/// Change in the score and ranking of a player.
+#[derive(Debug, Serialize)]
+pub struct PlayerScoreDiff {
+    pub player_id: String,
+    pub kind: PlayerKindDb,
+    pub old_score: i64,
+    pub new_score: i64,
+    pub old_ranking: Option<i32>,
+    pub new_ranking: Option<i32>,
+    /// Number of positions climbed by the player (negative if
+    /// the player lost positions).
+    pub ranking_delta: Option<i32>,
+}
+
+/// Recompute all scores and rankings, and return how they changed
+/// for each affected player. The database is left untouched.
+pub fn dry_run_recompute_task_scores(
+    conn: &mut db::Connection,
+    cx: Context,
+) -> anyhow::Result<Vec<PlayerScoreDiff>> {
+    rolled_back(conn, |conn| {
+        let old_standings = fetch_player_standings(conn)?;
+        recompute_task_scores(conn, cx)?;
+        players::update_rankings(conn)?;
+        let new_standings = fetch_player_standings(conn)?;
+
+        Ok(diff_player_standings(old_standings, new_standings))
+    })
+    .context("Failed to perform dry run of score recompute")
+}
+
+/// Time taken by each recompute strategy, and whether they agree.
+#[derive(Debug)]
+pub struct RecomputeStrategyComparison {
+    pub full_reset: Duration,
+    pub incremental: Duration,
+    /// Players whose score differs between both strategies.
+    pub mismatched_players: Vec<String>,
+}
+
+/// Recompute all scores with each strategy, and compare their timings
+/// and results. The database is left untouched.
+pub fn compare_recompute_strategies(
+    conn: &mut db::Connection,
+    cx: Context,
+) -> anyhow::Result<RecomputeStrategyComparison> {
+    let mut recompute_with = |strategy| {
+        rolled_back(conn, |conn| {
+            let elapsed = recompute_task_scores_with_strategy(conn, &cx, strategy)?;
+            Ok((elapsed, fetch_player_standings(conn)?))
+        })
+        .with_context(|| format!("Failed to recompute scores with strategy {strategy:?}"))
+    };
+
+    let (full_reset, full_reset_standings) = recompute_with(RecomputeStrategy::FullReset)?;
+    let (incremental, mut incremental_standings) = recompute_with(RecomputeStrategy::Incremental)?;
+
+    let mut mismatched_players: Vec<_> = full_reset_standings
+        .into_iter()
+        .filter_map(|(player_id, full_reset_standing)| {
+            let incremental_score = incremental_standings
+                .remove(&player_id)
+                .map(|standing| standing.score);
+            (incremental_score != Some(full_reset_standing.score)).then_some(player_id)
+        })
+        .collect();
+    // players only known to the incremental strategy
+    mismatched_players.extend(incremental_standings.into_keys());
+    mismatched_players.sort_unstable();
+
+    Ok(RecomputeStrategyComparison {
+        full_reset,
+        incremental,
+        mismatched_players,
+    })
+}
+
+/// Run `f` in a transaction that is always rolled back, and return
+/// its output.
+fn rolled_back<T, F>(conn: &mut db::Connection, f: F) -> anyhow::Result<T>
+where
+    F: FnOnce(&mut db::Connection) -> anyhow::Result<T>,
+{
+    use diesel::result::Error as DieselErr;
+    use diesel::Connection;
+
+    let mut output = None;
+
+    let result = conn.transaction(|conn| {
+        output = Some(f(conn)?);
+
+        // NB: always roll back, to discard any changes
+        Err::<(), _>(anyhow::Error::from(DieselErr::RollbackTransaction))
+    });
+
+    match (result, output) {
+        (Err(err), Some(output))
+            if matches!(
+                err.downcast_ref::<DieselErr>(),
+                Some(DieselErr::RollbackTransaction)
+            ) =>
+        {
+            Ok(output)
+        }
+        (Err(err), _) => Err(err),
+        (Ok(()), _) => unreachable!("Transaction is always rolled back"),
+    }
+}
+
+struct PlayerStanding {
+    kind: PlayerKindDb,
+    score: i64,
+    ranking: Option<i32>,
+}
+
+fn fetch_player_standings(
+    conn: &mut db::Connection,
+) -> anyhow::Result<HashMap<String, PlayerStanding>> {
+    use diesel::prelude::*;
+    use schema::player_ranks;
+    use schema::players;
+
+    let standings = players::table
+        .left_join(player_ranks::table)
+        .select((
+            players::dsl::id,
+            players::dsl::kind,
+            players::dsl::score,
+            player_ranks::dsl::ranking.nullable(),
+        ))
+        .load::<(String, PlayerKindDb, i64, Option<i32>)>(conn)
+        .context("Failed to fetch player standings from db")?
+        .into_iter()
+        .map(|(player_id, kind, score, ranking)| {
+            (
+                player_id,
+                PlayerStanding {
+                    kind,
+                    score,
+                    ranking,
+                },
+            )
+        })
+        .collect();
+
+    Ok(standings)
+}
+
+fn diff_player_standings(
+    old_standings: HashMap<String, PlayerStanding>,
+    mut new_standings: HashMap<String, PlayerStanding>,
+) -> Vec<PlayerScoreDiff> {
+    let mut diff: Vec<_> = old_standings
+        .into_iter()
+        .filter_map(|(player_id, old)| {
+            let new = new_standings.remove(&player_id)?;
+            let (old_score, old_ranking) = (old.score, old.ranking);
+            let (new_score, new_ranking) = (new.score, new.ranking);
+
+            if old_score == new_score && old_ranking == new_ranking {
+                return None;
+            }
+
+            Some(PlayerScoreDiff {
+                player_id,
+                kind: old.kind,
+                old_score,
+                new_score,
+                old_ranking,
+                new_ranking,
+                ranking_delta: old_ranking.zip(new_ranking).map(|(old, new)| old - new),
+            })
+        })
+        .collect();
+
+    diff.sort_unstable_by_key(|player_diff| {
+        (
+            player_diff.kind.to_string(),
+            player_diff.new_ranking.is_none(),
+            player_diff.new_ranking,
+            player_diff.player_id.clone(),
+        )
+    });
+
+    diff
+}
+
 #[inline]
 pub fn recompute_task_scores(conn: &mut db::Connection, cx: Context) -> anyhow::Result<()> {
+    recompute_task_scores_with_strategy(conn, &cx, cx.recompute_strategy())?;
+    Ok(())
+}
+
+/// Recompute the scores of all players with the given strategy, and
+/// return the time it took.
+///
+/// This must be called from within a transaction.
+fn recompute_task_scores_with_strategy(
+    conn: &mut db::Connection,
+    cx: &Context,
+    strategy: RecomputeStrategy,
+) -> anyhow::Result<Duration> {
+    let started_at = Instant::now();
+
     let pilots_with_nonzero_score = fetch_pilots_with_nonzero_score(conn)?;
-    reset_player_scores(conn).context("Failed to reset player scores")?;
-    recompute_completed_task_scores(conn, &cx)
-        .context("Failed to recompute completed task scores")?;
-    recompute_ongoing_task_scores(conn, &cx, &pilots_with_nonzero_score)
+
+    match strategy {
+        RecomputeStrategy::FullReset => {
+            reset_player_task_scores(conn)
+                .context("Failed to reset player task score breakdowns")?;
+            reset_player_scores(conn).context("Failed to reset player scores")?;
+            recompute_completed_task_scores(conn, cx)
+                .context("Failed to recompute completed task scores")?;
+        }
+        RecomputeStrategy::Incremental => {
+            create_next_task_scores_table(conn)
+                .context("Failed to prepare new player task score breakdowns")?;
+            insert_completed_task_scores(conn, cx)
+                .context("Failed to aggregate completed task scores")?;
+        }
+    }
+    recompute_ongoing_task_scores(conn, cx, &pilots_with_nonzero_score, strategy)
         .context("Failed to recompute ongoing task scores")?;
-    Ok(())
+
+    let rewritten_players = match strategy {
+        RecomputeStrategy::FullReset => None,
+        RecomputeStrategy::Incremental => {
+            sync_player_task_scores(conn)
+                .context("Failed to update player task score breakdowns")?;
+            Some(
+                apply_player_task_scores(conn)
+                    .context("Failed to apply task score breakdowns to player scores")?,
+            )
+        }
+    };
+
+    let elapsed = started_at.elapsed();
+    tracing::info!(
+        ?strategy,
+        ?elapsed,
+        ?rewritten_players,
+        "Recomputed task scores"
+    );
+
+    Ok(elapsed)
 }
 
 fn reset_player_scores(conn: &mut db::Connection) -> anyhow::Result<()> {
@@ -138,6 +456,172 @@ fn reset_player_scores(conn: &mut db::Connection) -> anyhow::Result<()> {
     Ok(())
 }
 
+fn reset_player_task_scores(conn: &mut db::Connection) -> anyhow::Result<()> {
+    use diesel::prelude::*;
+    use schema::player_task_scores;
+
+    diesel::delete(player_task_scores::table).execute(conn)?;
+    tracing::info!("Reset all player task score breakdowns");
+
+    Ok(())
+}
+
+/// Create the temporary table the task score breakdowns of the
+/// incremental strategy are computed into, before being merged into
+/// `player_task_scores`. It is dropped at the end of the current
+/// transaction.
+fn create_next_task_scores_table(conn: &mut db::Connection) -> anyhow::Result<()> {
+    use diesel::prelude::*;
+
+    diesel::sql_query("DROP TABLE IF EXISTS pg_temp.player_task_scores_next")
+        .execute(conn)
+        .context("Failed to drop previous task score breakdowns table")?;
+    diesel::sql_query(
+        r#"
+        CREATE TEMPORARY TABLE player_task_scores_next (
+            player_id VARCHAR NOT NULL,
+            task TASK_TYPE,
+            tx_kind TX_KIND,
+            pool_kind POOL_KIND NOT NULL,
+            pool_total DOUBLE PRECISION NOT NULL,
+            completed_by BIGINT NOT NULL,
+            share BIGINT NOT NULL,
+            upgrade VARCHAR NOT NULL DEFAULT ''
+        ) ON COMMIT DROP
+        "#,
+    )
+    .execute(conn)
+    .context("Failed to create task score breakdowns table")?;
+
+    Ok(())
+}
+
+/// Merge the task score breakdowns computed into
+/// `player_task_scores_next` with `player_task_scores`, only
+/// touching the rows that changed.
+fn sync_player_task_scores(conn: &mut db::Connection) -> anyhow::Result<()> {
+    use diesel::prelude::*;
+
+    const SAME_TASK: &str = r#"
+        next.player_id = player_task_scores.player_id
+        AND next.task IS NOT DISTINCT FROM player_task_scores.task
+        AND next.tx_kind IS NOT DISTINCT FROM player_task_scores.tx_kind
+        AND next.upgrade = player_task_scores.upgrade
+    "#;
+
+    let deleted_rows = diesel::sql_query(format!(
+        r#"
+        DELETE FROM player_task_scores
+        WHERE NOT EXISTS (
+            SELECT 1 FROM player_task_scores_next AS next
+            WHERE {SAME_TASK}
+        )
+        "#
+    ))
+    .execute(conn)
+    .context("Failed to delete stale task score breakdowns")?;
+
+    let updated_rows = diesel::sql_query(format!(
+        r#"
+        UPDATE player_task_scores
+        SET pool_kind = next.pool_kind,
+            pool_total = next.pool_total,
+            completed_by = next.completed_by,
+            share = next.share
+        FROM player_task_scores_next AS next
+        WHERE {SAME_TASK}
+          AND (next.pool_kind, next.pool_total, next.completed_by, next.share)
+              IS DISTINCT FROM
+              (player_task_scores.pool_kind, player_task_scores.pool_total,
+               player_task_scores.completed_by, player_task_scores.share)
+        "#
+    ))
+    .execute(conn)
+    .context("Failed to update changed task score breakdowns")?;
+
+    let inserted_rows = diesel::sql_query(format!(
+        r#"
+        INSERT INTO player_task_scores
+            ( player_id, task, tx_kind, pool_kind, pool_total, completed_by, share, upgrade )
+        SELECT next.player_id, next.task, next.tx_kind, next.pool_kind,
+               next.pool_total, next.completed_by, next.share, next.upgrade
+        FROM player_task_scores_next AS next
+        WHERE NOT EXISTS (
+            SELECT 1 FROM player_task_scores
+            WHERE {SAME_TASK}
+        )
+        "#
+    ))
+    .execute(conn)
+    .context("Failed to insert new task score breakdowns")?;
+
+    tracing::info!(
+        deleted_rows,
+        updated_rows,
+        inserted_rows,
+        "Updated player task score breakdowns"
+    );
+
+    Ok(())
+}
+
+fn insert_player_task_score(
+    conn: &mut db::Connection,
+    player_id: &str,
+    task_type: &Either<UnidentifiedTask, IdentifiedTask>,
+    upgrade: &str,
+    task_share: &TaskShare,
+    strategy: RecomputeStrategy,
+) -> anyhow::Result<()> {
+    use diesel::prelude::*;
+    use diesel::sql_types::{BigInt, Double, Nullable, Varchar};
+    use schema::player_task_scores;
+    use schema::sql_types;
+
+    let (tx_kind, task) = match task_type {
+        Left(UnidentifiedTask(tx_kind)) => (Some(*tx_kind), None),
+        Right(IdentifiedTask(task)) => (None, Some(*task)),
+    };
+
+    if let RecomputeStrategy::Incremental = strategy {
+        diesel::sql_query(
+            r#"
+            INSERT INTO player_task_scores_next
+                ( player_id, task, tx_kind, pool_kind, pool_total, completed_by, share, upgrade )
+            VALUES ( $1, $2, $3, $4, $5, $6, $7, $8 )
+            "#,
+        )
+        .bind::<Varchar, _>(player_id)
+        .bind::<Nullable<sql_types::TaskType>, _>(task)
+        .bind::<Nullable<sql_types::TxKind>, _>(tx_kind)
+        .bind::<sql_types::PoolKind, _>(task_share.pool_kind)
+        .bind::<Double, _>(task_share.pool_total)
+        .bind::<BigInt, _>(task_share.completed_by)
+        .bind::<BigInt, _>(task_share.share)
+        .bind::<Varchar, _>(upgrade)
+        .execute(conn)
+        .with_context(|| format!("Failed to record task score breakdown of {player_id}"))?;
+
+        return Ok(());
+    }
+
+    diesel::insert_into(player_task_scores::table)
+        .values(&PlayerTaskScoreInsertDb {
+            player_id: player_id.to_owned(),
+            task,
+            tx_kind,
+            pool_kind: task_share.pool_kind,
+            pool_total: task_share.pool_total,
+            completed_by: task_share.completed_by,
+            share: task_share.share,
+            upgrade: upgrade.to_owned(),
+        })
+        .execute(conn)
+        .with_context(|| format!("Failed to record task score breakdown of {player_id}"))?;
+
+    Ok(())
+}
+
 fn set_assign_player_score(
     conn: &mut db::Connection,
     player_id: &str,
@@ -167,30 +651,41 @@ fn recompute_ongoing_task_scores(
     conn: &mut db::Connection,
     cx: &Context,
     pilots_with_nonzero_score: &HashSet<String>,
+    strategy: RecomputeStrategy,
 ) -> anyhow::Result<()> {
-    recompute_gov_task_scores(conn, cx)
+    recompute_gov_task_scores(conn, cx, strategy)
         .context("Failed to recompute governance participation task scores")?;
-    recompute_uptime_task_scores(conn, cx, pilots_with_nonzero_score)
+    recompute_uptime_task_scores(conn, cx, pilots_with_nonzero_score, strategy)
         .context("Failed to recompute uptime task scores")?;
     Ok(())
 }
 
-fn recompute_gov_task_scores(conn: &mut db::Connection, cx: &Context) -> anyhow::Result<()> {
+fn recompute_gov_task_scores(
+    conn: &mut db::Connection,
+    cx: &Context,
+    strategy: RecomputeStrategy,
+) -> anyhow::Result<()> {
     let mut pilots_with_gov_participation_over_90 = HashMap::with_capacity(NUMBER_PILOTS);
 
     // compute who finished gov participation tasks this round
-    process_all_pilots(conn, |transaction_conn, PlayerId(player_id)| {
-        let participation_rate =
-            compute_governance_participation_rate(transaction_conn, &player_id).with_context(
-                || format!("Failed to compute governance participation rate of pilot {player_id}"),
-            )?;
-
-        if participation_rate >= 0.90 {
-            pilots_with_gov_participation_over_90.insert(player_id, participation_rate);
-        }
+    process_all_pilots_with_nonnull_validator_addr(
+        conn,
+        |transaction_conn, PlayerId(player_id), pilot_addr| {
+            let participation_rate =
+                compute_governance_participation_rate(transaction_conn, &player_id, &pilot_addr)
+                    .with_context(|| {
+                        format!(
+                            "Failed to compute governance participation rate of pilot {player_id}"
+                        )
+                    })?;
+
+            if participation_rate >= 0.90 {
+                pilots_with_gov_participation_over_90.insert(player_id, participation_rate);
+            }
 
-        Ok(())
-    })?;
+            Ok(())
+        },
+    )?;
 
     let no_gov_participation_rate_over_90 =
         CompletedBy(pilots_with_gov_participation_over_90.len() as _);
@@ -211,6 +706,8 @@ fn recompute_gov_task_scores(conn: &mut db::Connection, cx: &Context) -> anyhow:
             Right(IdentifiedTask(
                 TaskTypeDb::Keep90PerCentGovParticipationRate,
             )),
+            NO_UPGRADE,
+            strategy,
         )?;
 
         if participation_rate >= 0.99 {
@@ -222,6 +719,8 @@ fn recompute_gov_task_scores(conn: &mut db::Connection, cx: &Context) -> anyhow:
                 Right(IdentifiedTask(
                     TaskTypeDb::Keep99PerCentGovParticipationRate,
                 )),
+                NO_UPGRADE,
+                strategy,
             )?;
         }
     }
@@ -233,6 +732,7 @@ fn recompute_uptime_task_scores(
     conn: &mut db::Connection,
     cx: &Context,
     pilots_with_nonzero_score: &HashSet<String>,
+    strategy: RecomputeStrategy,
 ) -> anyhow::Result<()> {
     let mut pilots_with_uptime_over_95 = HashMap::with_capacity(NUMBER_PILOTS);
 
@@ -244,7 +744,7 @@ fn recompute_uptime_task_scores(
                 return Ok(());
             }
 
-            let uptime = compute_pilot_uptime(transaction_conn, pilot_addr)
+            let uptime = compute_pilot_uptime(transaction_conn, cx, pilot_addr)
                 .with_context(|| format!("Failed to compute uptime of pilot {player_id}"))?;
 
             if uptime >= 0.95 {
@@ -271,6 +771,8 @@ fn recompute_uptime_task_scores(
             no_uptime_over_95,
             cx,
             Right(IdentifiedTask(TaskTypeDb::Keep95PerCentUptime)),
+            NO_UPGRADE,
+            strategy,
         )?;
 
         if uptime >= 0.99 {
@@ -280,6 +782,8 @@ fn recompute_uptime_task_scores(
                 no_uptime_over_99,
                 cx,
                 Right(IdentifiedTask(TaskTypeDb::Keep99PerCentUptime)),
+                NO_UPGRADE,
+                strategy,
             )?;
         }
     }
@@ -293,7 +797,10 @@ fn recompute_completed_task_scores(conn: &mut db::Connection, cx: &Context) -> a
         |transaction_conn,
          num_completed_players,
          TaskDb {
-             task, player_id, ..
+             task,
+             player_id,
+             upgrade,
+             ..
          }| {
             tracing::info!(player_id, "Processing player's identified task score");
             update_score(
@@ -302,6 +809,8 @@ fn recompute_completed_task_scores(conn: &mut db::Connection, cx: &Context) -> a
                 num_completed_players,
                 cx,
                 Right(IdentifiedTask(task)),
+                &upgrade,
+                RecomputeStrategy::FullReset,
             )
         },
     )
@@ -321,6 +830,8 @@ fn recompute_completed_task_scores(conn: &mut db::Connection, cx: &Context) -> a
                 num_completed_players,
                 cx,
                 Left(UnidentifiedTask(tx_kind)),
+                NO_UPGRADE,
+                RecomputeStrategy::FullReset,
             )
         },
     )
@@ -335,195 +846,48 @@ fn update_score(
     num_completed_players: CompletedBy,
     cx: &Context,
     task_type: Either<UnidentifiedTask, IdentifiedTask>,
+    upgrade: &str,
+    strategy: RecomputeStrategy,
 ) -> anyhow::Result<()> {
     let player_kind = cx.player_kinds().get_or_update(player_id, conn)?;
-    let Some(pool_prize) = get_task_pool_prize(&player_kind, task_type.as_ref()) else {
+    let Some(pool_prize) = get_task_pool_prize(cx, &player_kind, task_type.as_ref()) else {
         return anyhow::Ok(());
     };
 
-    match pool_prize {
-        PoolPrizeKind::FixedCrew(prize) => prize.update_score(conn, player_id),
-        PoolPrizeKind::FixedPilot(prize) => prize.update_score(conn, player_id),
-        PoolPrizeKind::RelativeCrew(prize) => {
-            prize.update_score(conn, player_id, num_completed_players)
-        }
-        PoolPrizeKind::RelativePilot(prize) => {
-            prize.update_score(conn, player_id, num_completed_players)
-        }
+    let task_share = match pool_prize {
+        PoolPrizeKind::FixedCrew(prize) => prize.task_share(num_completed_players),
+        PoolPrizeKind::FixedPilot(prize) => prize.task_share(num_completed_players),
+        PoolPrizeKind::RelativeCrew(prize) => prize.task_share(num_completed_players),
+        PoolPrizeKind::RelativePilot(prize) => prize.task_share(num_completed_players),
+    };
+    tracing::info!(
+        player_id,
+        share = task_share.share,
+        "Computed score shares for player"
+    );
+
+    insert_player_task_score(conn, player_id, &task_type, upgrade, &task_share, strategy)?;
+
+    match strategy {
+        RecomputeStrategy::FullReset => set_assign_player_score(conn, player_id, task_share.share),
+        // NB: player scores are rewritten from their task score
+        // breakdowns once all of them have been computed
+        RecomputeStrategy::Incremental => Ok(()),
     }
 }
 
 fn get_task_pool_prize(
+    cx: &Context,
     player_kind: &PlayerKindDb,
     task_type: Either<&UnidentifiedTask, &IdentifiedTask>,
 ) -> Option<PoolPrizeKind> {
     use PlayerKindDb::*;
-    use TaskTypeDb::*;
-
-    const fn relative(points: f64) -> Score {
-        Score::RelativeToCompletion(points)
-    }
-
-    const fn fixed(points: f64) -> Score {
-        Score::Fixed(points)
-    }
 
-    let completable_by = &CompletableBy::check(task_type.map_either(
+    let task_type = task_type.map_either(
         |UnidentifiedTask(tx_kind)| tx_kind,
         |IdentifiedTask(task_type)| task_type,
-    ));
-
-    let cannot_be_assigned_points = matches!(
-        (completable_by, player_kind),
-        (CompletableBy::NoOne, _)
-            | (CompletableBy::OnlyCrew, Pilot)
-            | (CompletableBy::OnlyPilots, Crew)
     );
-
-    if cannot_be_assigned_points {
-        return None;
-    }
-
-    let pool_prize = task_type.either(
-        |UnidentifiedTask(tx_type)| match (player_kind, tx_type) {
-            (_, TransactionKindDb::Unknown) => None,
-            (Crew, _) => Some(relative({
-                // = 300_000_000_000.0 / (len(TransactionKindDb) - 1)
-                // = 300_000_000_000.0 / 26
-                11538461538.461538
-            })),
-            (Pilot, _) => Some(relative({
-                // = 250_000_000_000.0 / (len(TransactionKindDb) - 1)
-                // = 250_000_000_000.0 / 26
-                9615384615.384615
-            })),
-        },
-        |IdentifiedTask(task_type)| match task_type {
-            DelegateStakeOnV0 => Some(fixed(42_857_142_857.14)),
-            DelegateStakeOnV1 => Some(fixed(42_857_142_857.14)),
-            ClaimPosRewards => Some(relative(42_857_142_857.14)),
-            ShieldNaan => Some(relative(42_857_142_857.14)),
-            UnshieldNaan => Some(relative(42_857_142_857.14)),
-            ShieldToShielded => Some(relative(42_857_142_857.14)),
-            ShieldAssetOverIbc => Some(relative(42_857_142_857.14)),
-            SubmitPreGenesisBondTx => Some(relative(10_000_000_000.0)),
-            StartNode5MinFromGenesis => Some(relative(34_285_714_286.0)),
-            InitPostGenesisValidator => Some(relative(34_285_714_286.0)),
-            InValidatorSetFor1Epoch => Some(relative(34_285_714_286.0)),
-            VotePgfStewardProposal => Some(relative(34_285_714_286.0)),
-            VoteUpgradeV0ToV1 => Some(relative(34_285_714_286.0)),
-            VoteUpgradeV1ToV2 => Some(relative(34_285_714_286.0)),
-            SignFirstBlockOfUpgradeToV2 => Some(relative(34_285_714_286.0)),
-            Keep99PerCentUptime => Some(relative(31_250_000_000.0)),
-            Keep95PerCentUptime => Some(relative(31_250_000_000.0)),
-            Keep99PerCentGovParticipationRate => Some(relative(31_250_000_000.0)),
-            Keep90PerCentGovParticipationRate => Some(relative(31_250_000_000.0)),
-            ProvidePublicRpcEndpoint => Some(relative(match player_kind {
-                Crew => 66_666_666_667.0,
-                Pilot => 62_500_000_000.0,
-            })),
-            OperateNamadaIndexer => Some(relative(match player_kind {
-                Crew => 66_666_666_667.0,
-                Pilot => 62_500_000_000.0,
-            })),
-            OperateNamadaInterface => Some(relative(match player_kind {
-                Crew => 66_666_666_667.0,
-                Pilot => 62_500_000_000.0,
-            })),
-            OperateCosmosTestnetRelayer => Some(relative(match player_kind {
-                Crew => 66_666_666_667.0,
-                Pilot => 62_500_000_000.0,
-            })),
-            OperateOsmosisTestnetRelayer => Some(relative(match player_kind {
-                Crew => 66_666_666_667.0,
-                Pilot => 62_500_000_000.0,
-            })),
-            OperateNobleTestnetRelayer => Some(relative(match player_kind {
-                Crew => 66_666_666_667.0,
-                Pilot => 62_500_000_000.0,
-            })),
-            OperateRelayerOnNetWithNfts => Some(relative(match player_kind {
-                Crew => 66_666_666_667.0,
-                Pilot => 62_500_000_000.0,
-            })),
-            OperateRelayerOnAnotherNet => Some(relative(match player_kind {
-                Crew => 66_666_666_667.0,
-                Pilot => 62_500_000_000.0,
-            })),
-            IntegrateSeInBlockExplorer => Some(relative(match player_kind {
-                Crew => 66_666_666_667.0,
-                Pilot => 62_500_000_000.0,
-            })),
-            IntegrateSeInBrowserWallet => Some(relative(match player_kind {
-                Crew => 66_666_666_667.0,
-                Pilot => 62_500_000_000.0,
-            })),
-            IntegrateSeInAndroidWallet => Some(relative(match player_kind {
-                Crew => 66_666_666_667.0,
-                Pilot => 62_500_000_000.0,
-            })),
-            IntegrateSeInIosWallet => Some(relative(match player_kind {
-                Crew => 66_666_666_667.0,
-                Pilot => 62_500_000_000.0,
-            })),
-            IntegrateSeInAnotherWallet => Some(relative(match player_kind {
-                Crew => 66_666_666_667.0,
-                Pilot => 62_500_000_000.0,
-            })),
-            SupportShieldedTxsInBlockExplorer => Some(relative(match player_kind {
-                Crew => 66_666_666_667.0,
-                Pilot => 62_500_000_000.0,
-            })),
-            SupportShieldedTxsInBrowserWallet => Some(relative(match player_kind {
-                Crew => 66_666_666_667.0,
-                Pilot => 62_500_000_000.0,
-            })),
-            SupportShieldedTxsInAndroidWallet => Some(relative(match player_kind {
-                Crew => 66_666_666_667.0,
-                Pilot => 62_500_000_000.0,
-            })),
-            SupportShieldedTxsInIosWallet => Some(relative(match player_kind {
-                Crew => 66_666_666_667.0,
-                Pilot => 62_500_000_000.0,
-            })),
-            BuildAdditionalFossTooling => Some(relative(match player_kind {
-                Crew => 66_666_666_667.0,
-                Pilot => 62_500_000_000.0,
-            })),
-            BuildWebAppWithShieldedActionOnIbcChain => Some(relative(match player_kind {
-                Crew => 66_666_666_667.0,
-                Pilot => 62_500_000_000.0,
-            })),
-            OsmosisFrontendShieldedSwaps => Some(relative(match player_kind {
-                Crew => 66_666_666_667.0,
-                Pilot => 62_500_000_000.0,
-            })),
-            AnotherAppWithShieldedActionOnIbcChain => Some(relative(match player_kind {
-                Crew => 66_666_666_667.0,
-                Pilot => 62_500_000_000.0,
-            })),
-            ReduceMaspProofGenTime => Some(relative(match player_kind {
-                Crew => 66_666_666_667.0,
-                Pilot => 62_500_000_000.0,
-            })),
-            IncreaseNoteScanSpeed => Some(relative(match player_kind {
-                Crew => 66_666_666_667.0,
-                Pilot => 62_500_000_000.0,
-            })),
-            FindAndProveNamSpecsFlaw => Some(relative(match player_kind {
-                Crew => 66_666_666_667.0,
-                Pilot => 62_500_000_000.0,
-            })),
-            OptimizeNamSmExecSpeed => Some(relative(match player_kind {
-                Crew => 66_666_666_667.0,
-                Pilot => 62_500_000_000.0,
-            })),
-            FindProtocolSecVulnerability => Some(relative(match player_kind {
-                Crew => 66_666_666_667.0,
-                Pilot => 62_500_000_000.0,
-            })),
-        },
-    )?;
+    let pool_prize = task_prize(cx, player_kind, task_type)?;
 
     tracing::info!(?player_kind, ?task_type, ?pool_prize, "Computed pool prize");
 
@@ -541,6 +905,218 @@ fn get_task_pool_prize(
     })
 }
 
+/// Return the pool prize of a task for players of the given kind,
+/// if they can be assigned points for it.
+fn task_prize(
+    cx: &Context,
+    player_kind: &PlayerKindDb,
+    task_type: Either<&TransactionKindDb, &TaskTypeDb>,
+) -> Option<Score> {
+    use PlayerKindDb::*;
+
+    let completable_by = &CompletableBy::check(task_type);
+
+    let cannot_be_assigned_points = matches!(
+        (completable_by, player_kind),
+        (CompletableBy::NoOne, _)
+            | (CompletableBy::OnlyCrew, Pilot)
+            | (CompletableBy::OnlyPilots, Crew)
+    );
+
+    if cannot_be_assigned_points {
+        return None;
+    }
+
+    cx.prize_schedule().get(player_kind, task_type)
+}
+
+/// Load the pool prizes of all tasks into the temporary `task_prizes`
+/// table, dropped at the end of the current transaction.
+fn create_task_prizes_table(conn: &mut db::Connection, cx: &Context) -> anyhow::Result<()> {
+    use diesel::prelude::*;
+    use diesel::sql_types::{BigInt, Double, Nullable};
+    use schema::sql_types;
+
+    diesel::sql_query("DROP TABLE IF EXISTS pg_temp.task_prizes")
+        .execute(conn)
+        .context("Failed to drop previous task prizes table")?;
+    diesel::sql_query(
+        r#"
+        CREATE TEMPORARY TABLE task_prizes (
+            task TASK_TYPE,
+            tx_kind TX_KIND,
+            player_kind PLAYER_KIND NOT NULL,
+            pool_kind POOL_KIND NOT NULL,
+            pool_total DOUBLE PRECISION NOT NULL,
+            -- number of players fixed pools are split among
+            fixed_split_among BIGINT
+        ) ON COMMIT DROP
+        "#,
+    )
+    .execute(conn)
+    .context("Failed to create task prizes table")?;
+
+    let task_types = TransactionKindDb::ALL
+        .iter()
+        .map(Left)
+        .chain(TaskTypeDb::ALL.iter().map(Right));
+
+    for task_type in task_types {
+        for player_kind in [PlayerKindDb::Crew, PlayerKindDb::Pilot] {
+            let Some(pool_prize) = task_prize(cx, &player_kind, task_type) else {
+                continue;
+            };
+            let (pool_kind, fixed_split_among) = match (&player_kind, pool_prize) {
+                (PlayerKindDb::Crew, Score::Fixed(_)) => {
+                    (PoolKindDb::Fixed, Some(NUMBER_CREW_MEMBERS as i64))
+                }
+                (PlayerKindDb::Pilot, Score::Fixed(_)) => {
+                    (PoolKindDb::Fixed, Some(NUMBER_PILOTS as i64))
+                }
+                (_, Score::RelativeToCompletion(_)) => (PoolKindDb::RelativeToCompletion, None),
+            };
+
+            diesel::sql_query(
+                r#"
+                INSERT INTO task_prizes
+                    ( task, tx_kind, player_kind, pool_kind, pool_total, fixed_split_among )
+                VALUES ( $1, $2, $3, $4, $5, $6 )
+                "#,
+            )
+            .bind::<Nullable<sql_types::TaskType>, _>(task_type.right())
+            .bind::<Nullable<sql_types::TxKind>, _>(task_type.left())
+            .bind::<sql_types::PlayerKind, _>(&player_kind)
+            .bind::<sql_types::PoolKind, _>(pool_kind)
+            .bind::<Double, _>(pool_prize.total())
+            .bind::<Nullable<BigInt>, _>(fixed_split_among)
+            .execute(conn)
+            .with_context(|| {
+                format!("Failed to insert the {player_kind} prize of {task_type:?}")
+            })?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Compute the task score breakdowns of all identified and unidentified
+/// tasks into `player_task_scores_next`, by joining per task completion
+/// counts with the task prizes.
+fn insert_completed_task_scores(conn: &mut db::Connection, cx: &Context) -> anyhow::Result<()> {
+    use diesel::prelude::*;
+
+    create_task_prizes_table(conn, cx)?;
+
+    // NB: completions are counted over all players, including
+    // banned ones, whose tasks are otherwise ignored
+    let identified_rows = diesel::sql_query(
+        r#"
+        WITH completions AS (
+            SELECT task, upgrade, COUNT(*) AS completed_by
+            FROM tasks
+            GROUP BY task, upgrade
+        )
+        INSERT INTO player_task_scores_next
+            ( player_id, task, upgrade, pool_kind, pool_total, completed_by, share )
+        SELECT player_id, task, upgrade, pool_kind, pool_total, completed_by,
+               TRUNC(pool_total / split_among)::BIGINT
+        FROM (
+            SELECT tasks.player_id, tasks.task, tasks.upgrade,
+                   task_prizes.pool_kind, task_prizes.pool_total,
+                   completions.completed_by,
+                   COALESCE(task_prizes.fixed_split_among, completions.completed_by)
+                     AS split_among
+            FROM tasks
+            INNER JOIN players ON players.id = tasks.player_id
+            INNER JOIN task_prizes
+              ON task_prizes.task = tasks.task
+             AND task_prizes.player_kind = players.kind
+            INNER JOIN completions
+              ON completions.task = tasks.task
+             AND completions.upgrade = tasks.upgrade
+            WHERE players.is_banned <> TRUE
+        ) AS shares
+        "#,
+    )
+    .execute(conn)
+    .context("Failed to aggregate identified task scores")?;
+
+    let unidentified_rows = diesel::sql_query(
+        r#"
+        WITH completions AS (
+            SELECT unidentified_tasks.tx_kind, players.kind, COUNT(*) AS completed_by
+            FROM unidentified_tasks
+            INNER JOIN players ON players.id = unidentified_tasks.player_id
+            GROUP BY unidentified_tasks.tx_kind, players.kind
+        )
+        INSERT INTO player_task_scores_next
+            ( player_id, tx_kind, pool_kind, pool_total, completed_by, share )
+        SELECT player_id, tx_kind, pool_kind, pool_total, completed_by,
+               TRUNC(pool_total / split_among)::BIGINT
+        FROM (
+            SELECT unidentified_tasks.player_id, unidentified_tasks.tx_kind,
+                   task_prizes.pool_kind, task_prizes.pool_total,
+                   completions.completed_by,
+                   COALESCE(task_prizes.fixed_split_among, completions.completed_by)
+                     AS split_among
+            FROM unidentified_tasks
+            INNER JOIN players ON players.id = unidentified_tasks.player_id
+            INNER JOIN task_prizes
+              ON task_prizes.tx_kind = unidentified_tasks.tx_kind
+             AND task_prizes.player_kind = players.kind
+            INNER JOIN completions
+              ON completions.tx_kind = unidentified_tasks.tx_kind
+             AND completions.kind = players.kind
+            WHERE players.is_banned <> TRUE
+        ) AS shares
+        "#,
+    )
+    .execute(conn)
+    .context("Failed to aggregate unidentified task scores")?;
+
+    tracing::info!(
+        identified_rows,
+        unidentified_rows,
+        "Aggregated completed task scores"
+    );
+
+    Ok(())
+}
+
+/// Set the score of each player to the sum of their task shares,
+/// and return the number of players whose score changed.
+fn apply_player_task_scores(conn: &mut db::Connection) -> anyhow::Result<usize> {
+    use diesel::prelude::*;
+
+    let rewritten_players = diesel::sql_query(
+        r#"
+        UPDATE players
+        SET score = new_scores.score
+        FROM (
+            SELECT players.id,
+                   CASE WHEN players.is_banned <> TRUE
+                        THEN COALESCE(task_scores.total, 0)
+                        ELSE 0
+                   END AS score
+            FROM players
+            LEFT JOIN (
+                SELECT player_id, SUM(share)::BIGINT AS total
+                FROM player_task_scores
+                GROUP BY player_id
+            ) AS task_scores ON task_scores.player_id = players.id
+        ) AS new_scores
+        WHERE players.id = new_scores.id
+          AND players.score <> new_scores.score
+        "#,
+    )
+    .execute(conn)
+    .context("Failed to update player scores")?;
+
+    tracing::info!(rewritten_players, "Applied task scores to players");
+
+    Ok(rewritten_players)
+}
+
 fn process_identified_tasks<F>(conn: &mut db::Connection, mut process: F) -> anyhow::Result<()>
 where
     F: FnMut(&mut db::Connection, CompletedBy, TaskDb) -> anyhow::Result<()>,
@@ -562,20 +1138,27 @@ where
         .context("Failed to fetch tasks from the database")?
         .try_for_each(|task| {
             let task = task.context("Failed to deserialize task from database")?;
-            let task_completed_player_num = CompletedBy(match completed_players.entry(task.task) {
-                hash_map::Entry::Occupied(occupied) => *occupied.get(),
-                hash_map::Entry::Vacant(vacant) => {
-                    let completed_num: i64 = tasks::table
-                        .filter(tasks::dsl::task.eq(task.task))
-                        .count()
-                        .first(conn)
-                        .optional()
-                        .context("Failed to query num of players that completed task")?
-                        .unwrap_or_default();
-                    vacant.insert(completed_num);
-                    completed_num
-                }
-            });
+            // NB: upgrade bound tasks are counted separately for each upgrade
+            let completed_players_key = (task.task, task.upgrade.clone());
+            let task_completed_player_num =
+                CompletedBy(match completed_players.entry(completed_players_key) {
+                    hash_map::Entry::Occupied(occupied) => *occupied.get(),
+                    hash_map::Entry::Vacant(vacant) => {
+                        let completed_num: i64 = tasks::table
+                            .filter(
+                                tasks::dsl::task
+                                    .eq(task.task)
+                                    .and(tasks::dsl::upgrade.eq(&task.upgrade)),
+                            )
+                            .count()
+                            .first(conn)
+                            .optional()
+                            .context("Failed to query num of players that completed task")?
+                            .unwrap_or_default();
+                        vacant.insert(completed_num);
+                        completed_num
+                    }
+                });
             process(conn, task_completed_player_num, task)
         })?;
 
@@ -634,33 +1217,41 @@ where
 fn compute_governance_participation_rate(
     conn: &mut db::Connection,
     player_id: &str,
+    pilot_addr: &PilotValidatorAddress,
 ) -> anyhow::Result<f64> {
-    use diesel::dsl::count_star;
     use diesel::prelude::*;
-    use schema::governance_proposals;
     use schema::governance_votes;
 
+    use crate::sql_ext::CountInnerDsl;
+
     tracing::info!(player_id, "Computing governance participation rate");
 
     let (no_of_votes, total_governance_proposals, participation_rate) = 'result: {
-        let total_governance_proposals: i64 = governance_proposals::table
-            .select(count_star())
-            .first(conn)
-            .optional()?
-            .unwrap_or_default();
+        let Some(validator_set_epochs) = pilot_validator_set_epochs(conn, pilot_addr)? else {
+            break 'result (0, 0, 0.0);
+        };
+
+        let total_governance_proposals: i64 = eligible_proposals(&validator_set_epochs)
+            .count_inner()
+            .get(conn)
+            .context("Failed to query no. of eligible governance proposals")?;
 
         if total_governance_proposals == 0 {
             break 'result (0, 0, 0.0);
         }
 
         let no_of_votes: i64 = governance_votes::table
-            .filter(governance_votes::dsl::player_id.eq(player_id))
-            // FIXME: ideally we count distinct proposal id votes  :-)
-            //.distinct_on(governance_votes::proposal_id)
-            .select(count_star())
-            .first(conn)
-            .optional()?
-            .unwrap_or_default();
+            .filter(
+                governance_votes::dsl::player_id.eq(player_id).and(
+                    governance_votes::dsl::proposal_id
+                        .eq_any(eligible_proposals(&validator_set_epochs)),
+                ),
+            )
+            .select(governance_votes::dsl::proposal_id)
+            .distinct()
+            .count_inner()
+            .get(conn)
+            .with_context(|| format!("Failed to query no. of proposals voted by {player_id}"))?;
 
         debug_assert!(no_of_votes <= total_governance_proposals);
         let participation_rate = no_of_votes as f64 / total_governance_proposals as f64;
@@ -681,58 +1272,88 @@ fn compute_governance_participation_rate(
 
 fn compute_pilot_uptime(
     conn: &mut db::Connection,
+    cx: &Context,
     pilot_addr: PilotValidatorAddress,
 ) -> anyhow::Result<f64> {
     use diesel::prelude::*;
+    use schema::blocks;
     use schema::commits;
-    use schema::players;
     use schema::tm_addresses;
 
     use crate::sql_ext::CountInnerDsl;
 
+    let uptime_window = cx.uptime_window();
+    let window_start_epoch = match uptime_window {
+        UptimeWindow::FromGenesis => Some(0),
+        UptimeWindow::FromValidatorSetEntry => {
+            pilot_validator_set_epochs(conn, &pilot_addr)?.map(|epochs| *epochs.start())
+        }
+    };
+
     let PilotValidatorAddress(pilot_addr) = pilot_addr;
 
-    tracing::info!(pilot_addr, "Computing pilot uptime");
+    tracing::info!(pilot_addr, ?uptime_window, "Computing pilot uptime");
+
+    let Some(window_start_epoch) = window_start_epoch else {
+        tracing::info!(
+            pilot_addr,
+            "Pilot never entered the validator set, setting uptime to zero"
+        );
+        return Ok(0.0);
+    };
+
+    let Some(window_end_height) = last_state::read_last_crawled_block(conn)? else {
+        tracing::info!(
+            pilot_addr,
+            "No blocks have been crawled, setting uptime to zero"
+        );
+        return Ok(0.0);
+    };
 
     let (signed_blocks, total_blocks, uptime) = {
-        const TOTAL_BLOCKS: i64 = 355326;
-        const TOTAL_BLOCKS_F64: f64 = 355326.0;
+        let blocks_in_window = blocks::table.filter(
+            blocks::dsl::epoch
+                .ge(window_start_epoch)
+                .and(blocks::dsl::height.le(window_end_height)),
+        );
+
+        let total_blocks: i64 = blocks_in_window
+            .select(blocks::dsl::id)
+            .count_inner()
+            .get(conn)
+            .context("Failed to query no. of blocks in the uptime window")?;
 
         let signed_blocks: i64 = {
-            let validator_addr_with_same_player_id = players::table
-                .filter(
-                    players::dsl::namada_validator_address
-                        .nullable()
-                        .eq(&pilot_addr),
-                )
-                .select(players::dsl::namada_validator_address);
+            let pilot_tm_addrs = tm_addresses::table
+                .filter(tm_addresses::dsl::validator_namada_address.eq(&pilot_addr))
+                .select(tm_addresses::dsl::tm_address);
 
-            let tm_addrs_with_same_player_id = tm_addresses::table
+            commits::table
                 .filter(
-                    tm_addresses::dsl::validator_namada_address
-                        .nullable()
-                        .eq_any(validator_addr_with_same_player_id),
+                    commits::dsl::address.eq_any(pilot_tm_addrs).and(
+                        commits::dsl::block_id.eq_any(blocks_in_window.select(blocks::dsl::id)),
+                    ),
                 )
-                .select(tm_addresses::dsl::tm_address);
-
-            let eligible_pilot_nam_addrs = commits::table
-                .filter(commits::dsl::address.eq_any(tm_addrs_with_same_player_id))
-                .select(commits::dsl::address);
-
-            eligible_pilot_nam_addrs
+                .select(commits::dsl::address)
                 .count_inner()
                 .get(conn)
                 .with_context(|| format!("Failed to query no. of blocks signed by {pilot_addr}"))?
         };
 
-        debug_assert!(signed_blocks <= TOTAL_BLOCKS);
-        let uptime = signed_blocks as f64 / TOTAL_BLOCKS_F64;
+        debug_assert!(signed_blocks <= total_blocks);
+        let uptime = if total_blocks == 0 {
+            0.0
+        } else {
+            signed_blocks as f64 / total_blocks as f64
+        };
 
-        (signed_blocks, TOTAL_BLOCKS, uptime)
+        (signed_blocks, total_blocks, uptime)
     };
 
     tracing::info!(
         pilot_addr,
+        window_start_epoch,
+        window_end_height,
         signed_blocks,
         total_blocks,
         uptime,
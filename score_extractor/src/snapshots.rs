@@ -0,0 +1,87 @@
+use anyhow::Context;
+use shared::orm::schema;
+
+use crate::db;
+use crate::last_state;
+
+/// Snapshot the scores and rankings of all players, if the last
+/// processed block belongs to an epoch that has yet to be snapshotted.
+///
+/// Snapshots are labelled with the epoch of the last processed block,
+/// and hold the standings of players the first time tasks have been
+/// processed up to that epoch. Returns the epoch of the new snapshot,
+/// if one was taken.
+pub fn snapshot_scores_on_new_epoch(conn: &mut db::Connection) -> anyhow::Result<Option<i32>> {
+    use chrono::offset::Utc;
+    use diesel::dsl::max;
+    use diesel::prelude::*;
+    use diesel::sql_types::{Integer, Timestamp};
+    use schema::blocks;
+    use schema::score_snapshots;
+
+    let Some(last_processed_height) = last_state::read_last_processed_tasks_block(conn)? else {
+        tracing::debug!("No blocks have been processed yet, skipping score snapshot");
+        return Ok(None);
+    };
+
+    let Some(epoch) = blocks::table
+        .filter(blocks::dsl::height.eq(last_processed_height))
+        .select(blocks::dsl::epoch)
+        .first::<i32>(conn)
+        .optional()
+        .context("Failed to query epoch of last processed block")?
+    else {
+        tracing::warn!(
+            last_processed_height,
+            "Last processed block is not in the database, skipping score snapshot"
+        );
+        return Ok(None);
+    };
+
+    let last_snapshot_epoch = score_snapshots::table
+        .select(max(score_snapshots::dsl::epoch))
+        .first::<Option<i32>>(conn)
+        .context("Failed to query epoch of last score snapshot")?;
+
+    if last_snapshot_epoch.is_some_and(|last_epoch| last_epoch >= epoch) {
+        tracing::debug!(epoch, "Scores already snapshotted for current epoch");
+        return Ok(None);
+    }
+
+    let no_of_players = diesel::sql_query(
+        r#"
+        INSERT INTO score_snapshots ( epoch, height, taken_at, player_id, score, ranking )
+        SELECT $1, $2, $3, players.id, players.score, player_ranks.ranking
+        FROM players
+        LEFT JOIN player_ranks ON player_ranks.player_id = players.id
+        "#,
+    )
+    .bind::<Integer, _>(epoch)
+    .bind::<Integer, _>(last_processed_height)
+    .bind::<Timestamp, _>(Utc::now().naive_utc())
+    .execute(conn)
+    .with_context(|| format!("Failed to snapshot scores of epoch {epoch}"))?;
+
+    tracing::info!(
+        epoch,
+        last_processed_height,
+        no_of_players,
+        "Took score snapshot of new epoch"
+    );
+
+    Ok(Some(epoch))
+}
+
+/// Return the epoch of the latest score snapshot taken at or
+/// before the given epoch.
+pub fn snapshot_epoch_as_of(conn: &mut db::Connection, epoch: i32) -> anyhow::Result<Option<i32>> {
+    use diesel::dsl::max;
+    use diesel::prelude::*;
+    use schema::score_snapshots;
+
+    score_snapshots::table
+        .filter(score_snapshots::dsl::epoch.le(epoch))
+        .select(max(score_snapshots::dsl::epoch))
+        .first::<Option<i32>>(conn)
+        .with_context(|| format!("Failed to query score snapshot as of epoch {epoch}"))
+}
@@ -1,19 +1,30 @@
+use std::collections::HashSet;
+
 use anyhow::Context as AnyhowContext;
-use borsh::BorshDeserialize;
 use either::*;
 use namada_core::types::address::MASP;
-use namada_core::types::token::Transfer as NamadaTransfer;
+use serde::Serialize;
+use shared::error::DecodeError;
+use shared::orm::block::BlockDb;
 use shared::orm::governance_proposals::GovernanceProposalKindDb;
 use shared::orm::players::PlayerKindDb;
 use shared::orm::schema;
-use shared::orm::tasks::{TaskInsertDb, TaskTypeDb, UnidentifiedTaskInsertDb};
-use shared::orm::transaction::TransactionKindDb;
-use shared::transaction::{Transaction, TransactionKind};
+use shared::orm::tasks::{TaskInsertDb, TaskTypeDb, UnidentifiedTaskInsertDb, NO_UPGRADE};
+use shared::orm::transaction::TransactionDb;
+use shared::orm::transaction::{TransactionExitStatusDb, TransactionKindDb};
+use shared::transaction::{
+    DecodedTx, RawMemo, Transaction, TransactionExitStatus, TransactionKind,
+};
 
 use crate::context::Context;
 use crate::db;
+use crate::last_state;
+use crate::memos;
+use crate::metrics::PendingMetrics;
 use crate::players::{player_exists, process_all_pilots_with_incomplete_tasks, PlayerId};
-use crate::sql_ext::ExistsInnerDsl;
+use crate::transactions::{
+    load_transactions_with_hash, process_transactions_in_range, MAX_BLOCKS_PER_BATCH,
+};
 
 pub enum CompletableBy {
     NoOne,
@@ -32,16 +43,25 @@ impl CompletableBy {
                 _ => CompletableBy::DependsOnPlayerKind,
             },
             |task_type| match task_type {
-                DelegateStakeOnV0 | DelegateStakeOnV1 | ClaimPosRewards | ShieldNaan
-                | UnshieldNaan | ShieldToShielded | ShieldAssetOverIbc => CompletableBy::OnlyCrew,
+                // superseded by their upgrade bound counterparts
+                DelegateStakeOnV0
+                | DelegateStakeOnV1
+                | VoteUpgradeV0ToV1
+                | VoteUpgradeV1ToV2
+                | SignFirstBlockOfUpgradeToV2 => CompletableBy::NoOne,
+                DelegateStakeBeforeUpgrade
+                | ClaimPosRewards
+                | ShieldNaan
+                | UnshieldNaan
+                | ShieldToShielded
+                | ShieldAssetOverIbc => CompletableBy::OnlyCrew,
                 SubmitPreGenesisBondTx
                 | StartNode5MinFromGenesis
                 | InitPostGenesisValidator
                 | InValidatorSetFor1Epoch
                 | VotePgfStewardProposal
-                | VoteUpgradeV0ToV1
-                | VoteUpgradeV1ToV2
-                | SignFirstBlockOfUpgradeToV2
+                | VoteUpgrade
+                | SignFirstBlockOfUpgrade
                 | Keep99PerCentUptime
                 | Keep95PerCentUptime
                 | Keep99PerCentGovParticipationRate
@@ -77,112 +97,302 @@ impl CompletableBy {
     }
 }
 
+/// Exit statuses of a transaction that allow it to complete a task.
+pub enum CreditedOnExitStatus {
+    /// The transaction must have been applied.
+    Applied,
+    /// The transaction must have been accepted. This is the case of
+    /// wrapper transactions, which are never applied themselves.
+    Accepted,
+}
+
+impl CreditedOnExitStatus {
+    pub fn check(task_type: Either<&TransactionKindDb, &TaskTypeDb>) -> Self {
+        task_type.either(
+            |tx_type| match tx_type {
+                TransactionKindDb::Wrapper => CreditedOnExitStatus::Accepted,
+                // every other kind is an inner tx, which takes effect once applied
+                _ => CreditedOnExitStatus::Applied,
+            },
+            // NB: tasks are completed by inner txs, or not by txs at all
+            |_task_type| CreditedOnExitStatus::Applied,
+        )
+    }
+
+    pub fn allows(&self, status: &TransactionExitStatus) -> bool {
+        matches!(
+            (self, status),
+            (
+                CreditedOnExitStatus::Applied,
+                TransactionExitStatus::Applied
+            ) | (
+                CreditedOnExitStatus::Accepted,
+                TransactionExitStatus::Accepted
+            )
+        )
+    }
+}
+
+/// Reason why a transaction completed an unidentified task, rather
+/// than the identified task matching its kind.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum TaskDowngrade {
+    /// The identified task can only be completed by players of
+    /// another kind.
+    WrongPlayerKind {
+        task: TaskTypeDb,
+        player_kind: PlayerKindDb,
+    },
+    /// The vote was cast on a proposal that does not upgrade the network.
+    NonUpgradeProposal { proposal_id: u64 },
+    /// The bond was not made ahead of an upgrade offering delegation tasks.
+    OutsideEpochWindow { epoch: i32 },
+}
+
+/// Task completed by a transaction, as reported by a [`TaskDecision`].
+#[derive(Debug, Clone, Serialize)]
+pub struct DecidedTask {
+    pub player_id: String,
+    pub task: Option<TaskTypeDb>,
+    pub tx_kind: Option<TransactionKindDb>,
+    /// Network upgrade the task is completed for, if it is upgrade bound.
+    pub upgrade: Option<String>,
+    pub downgrade: Option<TaskDowngrade>,
+}
+
+/// Decision on the task completed by a transaction.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "decision", rename_all = "snake_case")]
+pub enum TaskDecision {
+    /// The transaction has no memo.
+    NoMemo,
+    /// The memo of the transaction is not a player id.
+    InvalidMemo { error: String },
+    /// The memo of the transaction refers to an unknown player.
+    UnknownPlayer { player_id: String },
+    /// The data of the transaction could not be decoded.
+    DecodeFailure { player_id: String, error: String },
+    /// The transaction votes on a proposal missing from the database.
+    UnknownProposal { player_id: String, proposal_id: u64 },
+    /// The task completed by the transaction cannot be completed by anyone.
+    NotCompletable(DecidedTask),
+    /// The exit status of the transaction does not credit the task.
+    NotCreditedOnExitStatus {
+        #[serde(flatten)]
+        task: DecidedTask,
+        status: TransactionExitStatusDb,
+    },
+    /// The block of the transaction has yet to be processed.
+    NotProcessed(DecidedTask),
+    /// The task had already been completed.
+    AlreadyCompleted {
+        #[serde(flatten)]
+        task: DecidedTask,
+        /// Transaction that completed the task, if known.
+        completed_by_tx: Option<String>,
+    },
+    /// The task was credited by the transaction.
+    Credited(DecidedTask),
+    /// The task should have been credited by the transaction, but
+    /// it is missing from the database, e.g. if it was revoked.
+    Missing(DecidedTask),
+}
+
+/// Task completed by a transaction, before it is checked against
+/// the transaction's exit status.
+struct ClassifiedTask {
+    player_id: String,
+    /// Either an unidentified task, or an identified task and the
+    /// upgrade it was completed for.
+    task_type: Either<TransactionKindDb, (TaskTypeDb, String)>,
+    downgrade: Option<TaskDowngrade>,
+}
+
+impl ClassifiedTask {
+    fn decided(&self) -> DecidedTask {
+        let (task, tx_kind, upgrade) = match &self.task_type {
+            Left(tx_kind) => (None, Some(*tx_kind), None),
+            Right((task, upgrade)) => (
+                Some(*task),
+                None,
+                Some(upgrade.clone()).filter(|upgrade| upgrade != NO_UPGRADE),
+            ),
+        };
+        DecidedTask {
+            player_id: self.player_id.clone(),
+            task,
+            tx_kind,
+            upgrade,
+            downgrade: self.downgrade.clone(),
+        }
+    }
+}
+
+/// Task to be inserted in the database for a transaction.
+struct InsertableTask {
+    classified: ClassifiedTask,
+    insertion: Either<UnidentifiedTaskInsertDb, TaskInsertDb>,
+}
+
+/// Compute the task to be inserted in the database for the given
+/// transaction, or the decision not to credit any task.
 fn compute_insertable_task_from_tx(
     conn: &mut db::Connection,
     cx: &Context,
     transaction: Transaction<PlayerId>,
-) -> anyhow::Result<Option<Either<UnidentifiedTaskInsertDb, TaskInsertDb>>> {
+) -> anyhow::Result<Result<InsertableTask, TaskDecision>> {
+    let tx_id = transaction.hash.to_string();
+    let tx_status = transaction.status.clone();
+
+    let classified = match classify_task_from_tx(conn, cx, transaction)? {
+        Ok(classified) => classified,
+        Err(decision) => return Ok(Err(decision)),
+    };
+    let ClassifiedTask {
+        player_id,
+        task_type,
+        ..
+    } = &classified;
+
+    let credited_on =
+        CreditedOnExitStatus::check(task_type.as_ref().map_right(|(task, _upgrade)| task));
+
+    if !credited_on.allows(&tx_status) {
+        tracing::debug!(
+            %tx_id,
+            %tx_status,
+            ?task_type,
+            "Ignoring task from tx whose exit status does not credit it"
+        );
+        return Ok(Err(TaskDecision::NotCreditedOnExitStatus {
+            task: classified.decided(),
+            status: TransactionExitStatusDb::from(&tx_status),
+        }));
+    }
+
+    let block = block_of_tx(conn, &tx_id)?;
+
+    let insertion = task_type.clone().map_either(
+        |tx_kind| UnidentifiedTaskInsertDb {
+            tx_kind,
+            player_id: player_id.clone(),
+            completed_at_height: Some(block.height),
+            completed_at: Some(block.included_at),
+            completed_by_tx: Some(tx_id.clone()),
+        },
+        |(task, upgrade)| TaskInsertDb {
+            task,
+            player_id: player_id.clone(),
+            upgrade,
+            completed_at_height: Some(block.height),
+            completed_at: Some(block.included_at),
+            completed_by_tx: Some(tx_id.clone()),
+        },
+    );
+
+    Ok(Ok(InsertableTask {
+        classified,
+        insertion,
+    }))
+}
+
+fn block_of_tx(conn: &mut db::Connection, tx_id: &str) -> anyhow::Result<BlockDb> {
+    use diesel::prelude::*;
+    use schema::blocks;
+    use schema::transactions;
+
+    // TODO: try to switch to `.single_value()` and
+    // replace `.eq_any()` with `.eq()`
+    let block_id_of_tx = transactions::table
+        .filter(transactions::dsl::id.eq(tx_id))
+        .select(transactions::dsl::block_id);
+
+    blocks::table
+        .filter(blocks::dsl::id.eq_any(block_id_of_tx))
+        .select(BlockDb::as_select())
+        .first(conn)
+        .context("Block should have been in database")
+}
+
+/// Classify the task completed by a transaction, or return the
+/// decision not to credit any task.
+fn classify_task_from_tx(
+    conn: &mut db::Connection,
+    cx: &Context,
+    transaction: Transaction<PlayerId>,
+) -> anyhow::Result<Result<ClassifiedTask, TaskDecision>> {
     let Some(PlayerId(player_id)) = transaction.memo else {
-        return Ok(None);
+        return Ok(Err(TaskDecision::NoMemo));
     };
 
     if !player_exists(conn, &player_id)? {
-        return Ok(None);
+        return Ok(Err(TaskDecision::UnknownPlayer { player_id }));
     }
 
+    let decode_failure = |player_id: &str, err: DecodeError| TaskDecision::DecodeFailure {
+        player_id: player_id.to_owned(),
+        error: err.to_string(),
+    };
+
+    // name of the upgrade the task is completed for, if any
+    let mut upgrade = NO_UPGRADE.to_owned();
+    // reason why an unidentified task is completed, if any
+    let mut downgrade = None;
+
     let kind = match &transaction.kind {
         TransactionKind::Bond(_) => {
-            use diesel::prelude::*;
-            use schema::blocks;
-            use schema::transactions;
-
-            let tx_id = transaction.hash.to_string();
-            let tx_epoch: i32 = {
-                // TODO: try to switch to `.single_value()` and
-                // replace `.eq_any()` with `.eq()`
-                let block_id_of_tx = transactions::table
-                    .filter(transactions::dsl::id.eq(&tx_id))
-                    .select(transactions::dsl::block_id);
-
-                blocks::table
-                    .filter(blocks::dsl::id.eq_any(block_id_of_tx))
-                    .select(blocks::dsl::epoch)
-                    .first(conn)
-                    .context("Block should have been in database")?
-            };
-
-            match () {
-                _ if tx_epoch < cx.epochs().v0_to_v1.0 as i32 => {
-                    Right(TaskTypeDb::DelegateStakeOnV0)
-                }
-                _ if tx_epoch < cx.epochs().v1_to_v2.0 as i32 => {
-                    Right(TaskTypeDb::DelegateStakeOnV1)
+            let tx_epoch = block_of_tx(conn, &transaction.hash.to_string())?.epoch;
+
+            match cx.campaign().next_upgrade_after(tx_epoch as u64) {
+                Some(next_upgrade)
+                    if next_upgrade.offers(TaskTypeDb::DelegateStakeBeforeUpgrade) =>
+                {
+                    upgrade = next_upgrade.name.clone();
+                    Right(TaskTypeDb::DelegateStakeBeforeUpgrade)
                 }
                 _ => {
-                    return Ok(Some(Left(UnidentifiedTaskInsertDb {
-                        player_id,
-                        tx_kind: TransactionKindDb::Bond,
-                    })))
+                    downgrade = Some(TaskDowngrade::OutsideEpochWindow { epoch: tx_epoch });
+                    Left(TransactionKindDb::Bond)
                 }
             }
         }
         TransactionKind::IbcShieldedTransfer(_) => Right(TaskTypeDb::ShieldAssetOverIbc),
-        TransactionKind::ShieldedTransfer(data) => {
-            let Some(transfer) = NamadaTransfer::try_from_slice(data)
-                .map_err(|err| {
-                    tracing::warn!(
-                        %err,
-                        "Failed to deserialize a Namada transfer from an indexed masp tx"
-                    );
-                })
-                .ok()
-            else {
-                return Ok(None);
+        TransactionKind::ShieldedTransfer(_) => {
+            let transfer = match transaction.kind.decode() {
+                Ok(DecodedTx::Transfer(transfer)) => transfer,
+                Ok(_) => unreachable!("Shielded transfers are decoded as transfers"),
+                Err(err) => return Ok(Err(decode_failure(&player_id, err))),
             };
 
-            match (transfer.source, transfer.target) {
-                (MASP, MASP) => Right(TaskTypeDb::ShieldToShielded),
-                (src, MASP) if src == cx.address_book().naan => Right(TaskTypeDb::ShieldNaan),
-                (MASP, dst) if dst == cx.address_book().naan => Right(TaskTypeDb::UnshieldNaan),
-                (_source, _target) => {
-                    return Ok(Some(Left(UnidentifiedTaskInsertDb {
-                        player_id,
-                        tx_kind: TransactionKindDb::ShieldedTransfer,
-                    })))
-                }
+            match (&transfer.source, &transfer.target) {
+                (&MASP, &MASP) => Right(TaskTypeDb::ShieldToShielded),
+                (src, &MASP) if *src == cx.address_book().naan => Right(TaskTypeDb::ShieldNaan),
+                (&MASP, dst) if *dst == cx.address_book().naan => Right(TaskTypeDb::UnshieldNaan),
+                (_source, _target) => Left(TransactionKindDb::ShieldedTransfer),
             }
         }
         TransactionKind::BecomeValidator(_) => Right(TaskTypeDb::InitPostGenesisValidator),
         TransactionKind::ClaimRewards(_) => Right(TaskTypeDb::ClaimPosRewards),
-        TransactionKind::ProposalVote(data) => 'proposal_kind: {
+        TransactionKind::ProposalVote(_) => 'proposal_kind: {
             use diesel::prelude::*;
-            use namada_governance::VoteProposalData;
             use schema::governance_proposals;
             use schema::governance_votes;
 
             const REGULAR_PROPOSAL: Either<TransactionKindDb, TaskTypeDb> =
                 Left(TransactionKindDb::ProposalVote);
-            const V0_TO_V1_PROPOSAL: Either<TransactionKindDb, TaskTypeDb> =
-                Right(TaskTypeDb::VoteUpgradeV0ToV1);
-            const V1_TO_V2_PROPOSAL: Either<TransactionKindDb, TaskTypeDb> =
-                Right(TaskTypeDb::VoteUpgradeV1ToV2);
             const PGF_STEWARD_PROPOSAL: Either<TransactionKindDb, TaskTypeDb> =
                 Right(TaskTypeDb::VotePgfStewardProposal);
 
-            let Some(data) = VoteProposalData::try_from_slice(data)
-                .map_err(|err| {
-                    tracing::warn!(
-                        %err,
-                        "Failed to deserialize Namada governance proposal data from indexed tx"
-                    );
-                })
-                .ok()
-            else {
-                return Ok(None);
+            let data = match transaction.kind.decode() {
+                Ok(DecodedTx::ProposalVote(data)) => data,
+                Ok(_) => unreachable!("Proposal votes are decoded as proposal votes"),
+                Err(err) => return Ok(Err(decode_failure(&player_id, err))),
             };
 
-            if matches!(data.id, 316 | 385) {
-                break 'proposal_kind V0_TO_V1_PROPOSAL;
+            if let Some(voted_upgrade) = cx.campaign().upgrade_vote_by_proposal_id(data.id) {
+                upgrade = voted_upgrade.name.clone();
+                break 'proposal_kind Right(TaskTypeDb::VoteUpgrade);
             }
 
             let maybe_proposal_data = governance_votes::table
@@ -207,35 +417,35 @@ fn compute_insertable_task_from_tx(
                     proposal_data = ?data,
                     "No governance proposal in db matching given data"
                 );
-                return Ok(None);
+                return Ok(Err(TaskDecision::UnknownProposal {
+                    player_id,
+                    proposal_id: data.id,
+                }));
             };
 
             if matches!(proposal_kind, GovernanceProposalKindDb::PgfSteward) {
                 break 'proposal_kind PGF_STEWARD_PROPOSAL;
             }
 
-            if proposer_in_db != cx.address_book().upgrade_proposer.to_string() {
-                tracing::trace!(
-                    %player_id,
-                    upgrade_proposer = %cx.address_book().upgrade_proposer,
-                    %proposer_in_db,
-                    "Governance proposal is not from upgrade proposer"
-                );
-                break 'proposal_kind REGULAR_PROPOSAL;
-            }
-
-            match () {
-                _ if grace_epoch_in_db == cx.epochs().v0_to_v1.0 as i32 => V0_TO_V1_PROPOSAL,
-                _ if grace_epoch_in_db == cx.epochs().v1_to_v2.0 as i32 => V1_TO_V2_PROPOSAL,
-                _ => {
+            match cx
+                .campaign()
+                .upgrade_vote_by_author_and_grace_epoch(&proposer_in_db, grace_epoch_in_db as u64)
+            {
+                Some(voted_upgrade) => {
+                    upgrade = voted_upgrade.name.clone();
+                    Right(TaskTypeDb::VoteUpgrade)
+                }
+                None => {
                     tracing::trace!(
                         %player_id,
                         proposal_data = ?data,
+                        %proposer_in_db,
                         grace_epoch_in_db,
-                        v0_to_v1_epoch = ?cx.epochs().v0_to_v1,
-                        v1_to_v2_epoch = ?cx.epochs().v1_to_v2,
-                        "Proposal's grace epoch does not match any of the upgrade epochs"
+                        "Proposal's author and grace epoch do not match any upgrade vote"
                     );
+                    downgrade = Some(TaskDowngrade::NonUpgradeProposal {
+                        proposal_id: data.id,
+                    });
                     REGULAR_PROPOSAL
                 }
             }
@@ -243,41 +453,189 @@ fn compute_insertable_task_from_tx(
         kind => Left(kind.into()),
     };
 
-    match CompletableBy::check(kind.as_ref()) {
-        CompletableBy::DependsOnPlayerKind => (),
+    let wrong_player_kind = |task: Either<TransactionKindDb, TaskTypeDb>, player_kind| {
+        task.right()
+            .map(|task| TaskDowngrade::WrongPlayerKind { task, player_kind })
+    };
+
+    let task_type = match CompletableBy::check(kind.as_ref()) {
+        CompletableBy::DependsOnPlayerKind => kind,
         CompletableBy::NoOne => {
             tracing::trace!(
                 %player_id,
                 task_kind = ?kind,
                 "Ignoring task that cannot be completed"
             );
-            return Ok(None);
+            let not_completable = ClassifiedTask {
+                player_id,
+                task_type: kind.map_right(|task| (task, upgrade)),
+                downgrade,
+            };
+            return Ok(Err(TaskDecision::NotCompletable(not_completable.decided())));
         }
         CompletableBy::OnlyCrew => {
             let player_kind = cx.player_kinds().get_or_update(&player_id, conn)?;
-            if !matches!(player_kind, PlayerKindDb::Crew) {
-                return Ok(Some(Left(UnidentifiedTaskInsertDb {
-                    player_id,
-                    tx_kind: (&transaction.kind).into(),
-                })));
+            if matches!(player_kind, PlayerKindDb::Crew) {
+                kind
+            } else {
+                downgrade = wrong_player_kind(kind, player_kind);
+                Left((&transaction.kind).into())
             }
         }
         CompletableBy::OnlyPilots => {
             let player_kind = cx.player_kinds().get_or_update(&player_id, conn)?;
-            if !matches!(player_kind, PlayerKindDb::Pilot) {
-                return Ok(Some(Left(UnidentifiedTaskInsertDb {
-                    player_id,
-                    tx_kind: (&transaction.kind).into(),
-                })));
+            if matches!(player_kind, PlayerKindDb::Pilot) {
+                kind
+            } else {
+                downgrade = wrong_player_kind(kind, player_kind);
+                Left((&transaction.kind).into())
             }
         }
-    }
+    };
 
-    Ok(Some(kind.map_either_with(
+    Ok(Ok(ClassifiedTask {
         player_id,
-        |player_id, tx_kind| UnidentifiedTaskInsertDb { player_id, tx_kind },
-        |player_id, task| TaskInsertDb { player_id, task },
-    )))
+        task_type: task_type.map_right(|task| (task, upgrade)),
+        downgrade,
+    }))
+}
+
+/// Decision on the task completed by a stored transaction.
+#[derive(Debug, Serialize)]
+pub struct TxTaskDecision {
+    pub tx_hash: String,
+    pub tx_kind: TransactionKindDb,
+    pub block_height: i32,
+    pub decision: TaskDecision,
+}
+
+/// Explain why the transactions with the given hash, or wrapping an
+/// inner transaction with the given hash, did or did not complete a task.
+pub fn explain_tx_task_decisions(
+    conn: &mut db::Connection,
+    cx: &Context,
+    hash: &str,
+) -> anyhow::Result<Vec<TxTaskDecision>> {
+    let stored_txs = load_transactions_with_hash(conn, hash)?;
+    explain_task_decisions(conn, cx, stored_txs)
+}
+
+/// Explain why the transactions whose memo resolves to the given
+/// player did or did not complete a task. Only processed transactions
+/// are considered, since the player of a memo is resolved at that time.
+pub fn explain_player_task_decisions(
+    conn: &mut db::Connection,
+    cx: &Context,
+    player_id: &str,
+    offset: i64,
+    limit: i64,
+) -> anyhow::Result<Vec<TxTaskDecision>> {
+    use diesel::prelude::*;
+    use schema::blocks;
+    use schema::transactions;
+
+    let stored_txs = transactions::table
+        .inner_join(blocks::table)
+        .filter(transactions::dsl::player_id.eq(player_id))
+        .order((blocks::dsl::height, transactions::dsl::index))
+        .offset(offset)
+        .limit(limit)
+        .select((TransactionDb::as_select(), blocks::dsl::height))
+        .load::<(TransactionDb, i32)>(conn)
+        .with_context(|| format!("Failed to query transactions of player {player_id}"))?;
+
+    explain_task_decisions(conn, cx, stored_txs)
+}
+
+fn explain_task_decisions(
+    conn: &mut db::Connection,
+    cx: &Context,
+    stored_txs: Vec<(TransactionDb, i32)>,
+) -> anyhow::Result<Vec<TxTaskDecision>> {
+    let last_processed_height = last_state::read_last_processed_tasks_block(conn)?;
+
+    stored_txs
+        .into_iter()
+        .map(|(stored_tx, block_height)| {
+            let transaction: Transaction<RawMemo> = stored_tx.into();
+            let tx_hash = transaction.hash.to_string();
+            let tx_kind = TransactionKindDb::from(&transaction.kind);
+
+            let resolved = match &transaction.memo {
+                Some(memo) => memos::resolve_memo(conn, cx.memo_parser(), memo)?
+                    .map(|resolved| Some(resolved.player_id)),
+                None => Ok(None),
+            };
+            let insertable = match resolved {
+                Ok(player_id) => {
+                    compute_insertable_task_from_tx(conn, cx, transaction.with_memo(player_id))?
+                }
+                Err(err) => Err(TaskDecision::InvalidMemo {
+                    error: err.to_string(),
+                }),
+            };
+            let processed = last_processed_height.is_some_and(|height| block_height <= height);
+            let decision = match insertable {
+                Err(decision) => decision,
+                Ok(InsertableTask { classified, .. }) if !processed => {
+                    TaskDecision::NotProcessed(classified.decided())
+                }
+                Ok(InsertableTask {
+                    classified,
+                    insertion,
+                }) => match read_completing_tx_of_task(conn, &insertion)? {
+                    None => TaskDecision::Missing(classified.decided()),
+                    Some(Some(completed_by_tx)) if completed_by_tx == tx_hash => {
+                        TaskDecision::Credited(classified.decided())
+                    }
+                    Some(completed_by_tx) => TaskDecision::AlreadyCompleted {
+                        task: classified.decided(),
+                        completed_by_tx,
+                    },
+                },
+            };
+
+            Ok(TxTaskDecision {
+                tx_hash,
+                tx_kind,
+                block_height,
+                decision,
+            })
+        })
+        .collect()
+}
+
+/// Return the transaction that completed the given task, if the
+/// task is in the database.
+fn read_completing_tx_of_task(
+    conn: &mut db::Connection,
+    task: &Either<UnidentifiedTaskInsertDb, TaskInsertDb>,
+) -> anyhow::Result<Option<Option<String>>> {
+    use diesel::prelude::*;
+    use schema::tasks;
+    use schema::unidentified_tasks;
+
+    match task {
+        Left(unidentified) => unidentified_tasks::table
+            .filter(
+                unidentified_tasks::dsl::player_id
+                    .eq(&unidentified.player_id)
+                    .and(unidentified_tasks::dsl::tx_kind.eq(unidentified.tx_kind)),
+            )
+            .select(unidentified_tasks::dsl::completed_by_tx)
+            .first(conn),
+        Right(task) => tasks::table
+            .filter(
+                tasks::dsl::player_id
+                    .eq(&task.player_id)
+                    .and(tasks::dsl::task.eq(task.task))
+                    .and(tasks::dsl::upgrade.eq(&task.upgrade)),
+            )
+            .select(tasks::dsl::completed_by_tx)
+            .first(conn),
+    }
+    .optional()
+    .with_context(|| format!("Failed to query completed task {task:?}"))
 }
 
 #[derive(Debug)]
@@ -286,9 +644,13 @@ pub enum TaskInput<'a> {
     Transaction {
         tx: Transaction<PlayerId>,
         cx: &'a Context,
+        pending_metrics: &'a mut PendingMetrics,
     },
     /// Pilot input.
-    Pilot { cx: &'a Context },
+    Pilot {
+        cx: &'a Context,
+        pending_metrics: &'a mut PendingMetrics,
+    },
     /// Special tasks input.
     SpecialTasks,
 }
@@ -297,8 +659,15 @@ pub fn update_task_statuses(conn: &mut db::Connection, input: TaskInput) -> anyh
     tracing::debug!(?input, "Attempting to insert task into database");
 
     match input {
-        TaskInput::Transaction { tx, cx } => mark_task_completed_from_tx(conn, cx, tx),
-        TaskInput::Pilot { cx } => mark_completed_pilot_tasks(conn, cx),
+        TaskInput::Transaction {
+            tx,
+            cx,
+            pending_metrics,
+        } => mark_task_completed_from_tx(conn, cx, pending_metrics, tx).map(|_decision| ()),
+        TaskInput::Pilot {
+            cx,
+            pending_metrics,
+        } => mark_completed_pilot_tasks(conn, cx, pending_metrics),
         TaskInput::SpecialTasks => mark_completed_special_tasks(conn),
     }
 }
@@ -324,9 +693,12 @@ fn mark_completed_special_tasks(conn: &mut db::Connection) -> anyhow::Result<()>
     Ok(())
 }
 
-fn mark_completed_pilot_tasks(conn: &mut db::Connection, cx: &Context) -> anyhow::Result<()> {
-    let Some(genesis_time) = cx
-        .genesis_time()
+/// Return the time the chain started at, if known.
+fn read_genesis_time(
+    conn: &mut db::Connection,
+    cx: &Context,
+) -> anyhow::Result<Option<chrono::NaiveDateTime>> {
+    cx.genesis_time()
         .copied()
         .map(Ok)
         .or_else(|| {
@@ -341,8 +713,15 @@ fn mark_completed_pilot_tasks(conn: &mut db::Connection, cx: &Context) -> anyhow
                 .context("Failed to read genesis time from db")
                 .transpose()
         })
-        .transpose()?
-    else {
+        .transpose()
+}
+
+fn mark_completed_pilot_tasks(
+    conn: &mut db::Connection,
+    cx: &Context,
+    pending_metrics: &mut PendingMetrics,
+) -> anyhow::Result<()> {
+    let Some(genesis_time) = read_genesis_time(conn, cx)? else {
         tracing::info!("No blocks have been committed yet, can't check pilot tasks");
         return Ok(());
     };
@@ -350,8 +729,11 @@ fn mark_completed_pilot_tasks(conn: &mut db::Connection, cx: &Context) -> anyhow
     process_all_pilots_with_incomplete_tasks(
         conn,
         TaskTypeDb::StartNode5MinFromGenesis,
+        NO_UPGRADE,
         |conn, PlayerId(player_id)| {
-            if signed_block_5min_after_genesis(conn, &genesis_time, &player_id)? {
+            if let Some(block) =
+                first_signed_block_5min_after_genesis(conn, &genesis_time, &player_id)?
+            {
                 use diesel::prelude::*;
                 use schema::tasks;
 
@@ -359,6 +741,10 @@ fn mark_completed_pilot_tasks(conn: &mut db::Connection, cx: &Context) -> anyhow
                     .values(&TaskInsertDb {
                         task: TaskTypeDb::StartNode5MinFromGenesis,
                         player_id: player_id.clone(),
+                        upgrade: NO_UPGRADE.to_owned(),
+                        completed_at_height: Some(block.height),
+                        completed_at: Some(block.included_at),
+                        completed_by_tx: None,
                     })
                     .on_conflict_do_nothing()
                     .execute(conn)
@@ -367,11 +753,12 @@ fn mark_completed_pilot_tasks(conn: &mut db::Connection, cx: &Context) -> anyhow
                 if affected_rows == 0 {
                     tracing::warn!(
                         player_id,
-                        upgrade_to_v2_grace_epoch = %cx.epochs().v1_to_v2,
                         "Pilot's \"signed block up to 5mins after genesis\" \
                          task somehow had already been completed"
                     );
                 } else {
+                    pending_metrics
+                        .inc_tasks_inserted(Right(&TaskTypeDb::StartNode5MinFromGenesis));
                     tracing::info!(
                         player_id,
                         genesis_time = ?cx.genesis_time(),
@@ -383,52 +770,69 @@ fn mark_completed_pilot_tasks(conn: &mut db::Connection, cx: &Context) -> anyhow
         },
     )?;
 
-    process_all_pilots_with_incomplete_tasks(
-        conn,
-        TaskTypeDb::SignFirstBlockOfUpgradeToV2,
-        |conn, PlayerId(player_id)| {
-            if signed_first_block_of_upgrade_to_v2(conn, cx.epochs().v1_to_v2.0 as i32, &player_id)?
-            {
-                use diesel::prelude::*;
-                use schema::tasks;
-
-                let affected_rows = diesel::insert_into(tasks::table)
-                    .values(&TaskInsertDb {
-                        task: TaskTypeDb::SignFirstBlockOfUpgradeToV2,
-                        player_id: player_id.clone(),
-                    })
-                    .on_conflict_do_nothing()
-                    .execute(conn)
-                    .context(
-                        "Failed to insert \"first block of upgrade \
-                         signed\" task into db",
-                    )?;
-
-                if affected_rows == 0 {
-                    tracing::warn!(
-                        player_id,
-                        upgrade_to_v2_grace_epoch = %cx.epochs().v1_to_v2,
-                        "Pilot's \"signed first block of upgrade to v2's grace epoch\" \
-                         task somehow had already been completed"
-                    );
-                } else {
-                    tracing::info!(
-                        player_id,
-                        upgrade_to_v2_grace_epoch = %cx.epochs().v1_to_v2,
-                        "Task completed - pilot signed first block of upgrade to v2's \
-                         grace epoch"
-                    );
+    for upgrade in cx
+        .campaign()
+        .upgrades()
+        .iter()
+        .filter(|upgrade| upgrade.offers(TaskTypeDb::SignFirstBlockOfUpgrade))
+    {
+        process_all_pilots_with_incomplete_tasks(
+            conn,
+            TaskTypeDb::SignFirstBlockOfUpgrade,
+            &upgrade.name,
+            |conn, PlayerId(player_id)| {
+                if let Some(block) =
+                    signed_first_block_of_upgrade(conn, upgrade.epoch as i32, &player_id)?
+                {
+                    use diesel::prelude::*;
+                    use schema::tasks;
+
+                    let affected_rows = diesel::insert_into(tasks::table)
+                        .values(&TaskInsertDb {
+                            task: TaskTypeDb::SignFirstBlockOfUpgrade,
+                            player_id: player_id.clone(),
+                            upgrade: upgrade.name.clone(),
+                            completed_at_height: Some(block.height),
+                            completed_at: Some(block.included_at),
+                            completed_by_tx: None,
+                        })
+                        .on_conflict_do_nothing()
+                        .execute(conn)
+                        .context(
+                            "Failed to insert \"first block of upgrade \
+                             signed\" task into db",
+                        )?;
+
+                    if affected_rows == 0 {
+                        tracing::warn!(
+                            player_id,
+                            upgrade = upgrade.name,
+                            upgrade_epoch = upgrade.epoch,
+                            "Pilot's \"signed first block of upgrade\" \
+                             task somehow had already been completed"
+                        );
+                    } else {
+                        pending_metrics
+                            .inc_tasks_inserted(Right(&TaskTypeDb::SignFirstBlockOfUpgrade));
+                        tracing::info!(
+                            player_id,
+                            upgrade = upgrade.name,
+                            upgrade_epoch = upgrade.epoch,
+                            "Task completed - pilot signed first block of upgrade"
+                        );
+                    }
                 }
-            }
-            Ok(())
-        },
-    )?;
+                Ok(())
+            },
+        )?;
+    }
 
     process_all_pilots_with_incomplete_tasks(
         conn,
         TaskTypeDb::InValidatorSetFor1Epoch,
+        NO_UPGRADE,
         |conn, PlayerId(player_id)| {
-            if signed_at_least_one_block(conn, &player_id)? {
+            if let Some(block) = first_signed_block(conn, &player_id)? {
                 use diesel::prelude::*;
                 use schema::tasks;
 
@@ -436,6 +840,10 @@ fn mark_completed_pilot_tasks(conn: &mut db::Connection, cx: &Context) -> anyhow
                     .values(&TaskInsertDb {
                         task: TaskTypeDb::InValidatorSetFor1Epoch,
                         player_id: player_id.clone(),
+                        upgrade: NO_UPGRADE.to_owned(),
+                        completed_at_height: Some(block.height),
+                        completed_at: Some(block.included_at),
+                        completed_by_tx: None,
                     })
                     .on_conflict_do_nothing()
                     .execute(conn)
@@ -444,11 +852,11 @@ fn mark_completed_pilot_tasks(conn: &mut db::Connection, cx: &Context) -> anyhow
                 if affected_rows == 0 {
                     tracing::warn!(
                         player_id,
-                        upgrade_to_v2_grace_epoch = %cx.epochs().v1_to_v2,
                         "Pilot's \"signed at least one block\" \
                          task somehow had already been completed"
                     );
                 } else {
+                    pending_metrics.inc_tasks_inserted(Right(&TaskTypeDb::InValidatorSetFor1Epoch));
                     tracing::info!(
                         player_id,
                         "Task completed - pilot signed at least one block"
@@ -462,8 +870,13 @@ fn mark_completed_pilot_tasks(conn: &mut db::Connection, cx: &Context) -> anyhow
     Ok(())
 }
 
-fn signed_at_least_one_block(conn: &mut db::Connection, player_id: &str) -> anyhow::Result<bool> {
+/// Return the first block signed by the given pilot.
+fn first_signed_block(
+    conn: &mut db::Connection,
+    player_id: &str,
+) -> anyhow::Result<Option<BlockDb>> {
     use diesel::prelude::*;
+    use schema::blocks;
     use schema::commits;
     use schema::players;
     use schema::tm_addresses;
@@ -485,36 +898,33 @@ fn signed_at_least_one_block(conn: &mut db::Connection, player_id: &str) -> anyh
         )
         .select(tm_addresses::dsl::tm_address);
 
-    let eligible_pilot_nam_addrs: bool = {
-        let eligible_pilot_nam_addrs = commits::table
-            .filter(commits::dsl::address.eq_any(tm_addrs_with_same_player_id))
-            .select(commits::dsl::address);
-
-        eligible_pilot_nam_addrs
-            .exists_inner()
-            .get(conn)
-            .with_context(|| {
-                format!(
-                    "Failed to check if pilot with id {player_id} signed \
-                         at least one block"
-                )
-            })?
-    };
+    let first_signed_block = blocks::table
+        .inner_join(commits::table)
+        .filter(commits::dsl::address.eq_any(tm_addrs_with_same_player_id))
+        .order(blocks::dsl::height.asc())
+        .select(BlockDb::as_select())
+        .first(conn)
+        .optional()
+        .with_context(|| {
+            format!("Failed to check if pilot with id {player_id} signed at least one block")
+        })?;
 
     tracing::info!(
         %player_id,
-        eligible_pilot_nam_addrs,
+        first_signed_height = first_signed_block.as_ref().map(|block| block.height),
         "Checking if player signed at least one block"
     );
 
-    Ok(eligible_pilot_nam_addrs)
+    Ok(first_signed_block)
 }
 
-fn signed_first_block_of_upgrade_to_v2(
+/// Return the first block of the given epoch, if it was signed by
+/// the given pilot.
+fn signed_first_block_of_upgrade(
     conn: &mut db::Connection,
-    v2_grace_epoch: i32,
+    upgrade_epoch: i32,
     player_id: &str,
-) -> anyhow::Result<bool> {
+) -> anyhow::Result<Option<BlockDb>> {
     use diesel::dsl::min;
     use diesel::prelude::*;
     use schema::blocks;
@@ -525,15 +935,9 @@ fn signed_first_block_of_upgrade_to_v2(
     diesel::alias!(blocks as blocks_alias: BlocksAlias);
 
     let min_height = blocks_alias
-        .filter(blocks_alias.field(blocks::dsl::epoch).eq(v2_grace_epoch))
+        .filter(blocks_alias.field(blocks::dsl::epoch).eq(upgrade_epoch))
         .select(min(blocks_alias.field(blocks::dsl::height)));
 
-    let eligible_pilot_tm_addrs = blocks::table
-        .inner_join(commits::table)
-        .filter(blocks::dsl::height.nullable().eq_any(min_height))
-        .group_by(commits::dsl::address)
-        .select(commits::dsl::address);
-
     let validator_addr_with_same_player_id = players::table
         .filter(
             players::dsl::namada_validator_address
@@ -543,42 +947,49 @@ fn signed_first_block_of_upgrade_to_v2(
         )
         .select(players::dsl::namada_validator_address);
 
-    let eligible_pilot_nam_addrs: bool = {
-        let eligible_pilot_nam_addrs = tm_addresses::table
-            .group_by(tm_addresses::dsl::validator_namada_address)
-            .filter(
-                tm_addresses::dsl::validator_namada_address
-                    .nullable()
-                    .eq_any(validator_addr_with_same_player_id)
-                    .and(tm_addresses::dsl::tm_address.eq_any(eligible_pilot_tm_addrs)),
+    let tm_addrs_with_same_player_id = tm_addresses::table
+        .filter(
+            tm_addresses::dsl::validator_namada_address
+                .nullable()
+                .eq_any(validator_addr_with_same_player_id),
+        )
+        .select(tm_addresses::dsl::tm_address);
+
+    let first_block_of_upgrade = blocks::table
+        .inner_join(commits::table)
+        .filter(
+            blocks::dsl::height
+                .nullable()
+                .eq_any(min_height)
+                .and(commits::dsl::address.eq_any(tm_addrs_with_same_player_id)),
+        )
+        .select(BlockDb::as_select())
+        .first(conn)
+        .optional()
+        .with_context(|| {
+            format!(
+                "Failed to check if pilot with id {player_id} signed \
+                 first block of upgrade at epoch {upgrade_epoch}"
             )
-            .select(tm_addresses::dsl::validator_namada_address);
-
-        eligible_pilot_nam_addrs
-            .exists_inner()
-            .get(conn)
-            .with_context(|| {
-                format!(
-                    "Failed to check if pilot with id {player_id} signed \
-                     first block since upgrade to v2 task"
-                )
-            })?
-    };
+        })?;
 
     tracing::info!(
         %player_id,
-        eligible_pilot_nam_addrs,
-        "Checking if player signed first block since upgrade to v2"
+        upgrade_epoch,
+        signed = first_block_of_upgrade.is_some(),
+        "Checking if player signed first block of upgrade"
     );
 
-    Ok(eligible_pilot_nam_addrs)
+    Ok(first_block_of_upgrade)
 }
 
-fn signed_block_5min_after_genesis(
+/// Return the first block signed by the given pilot up to 5 minutes
+/// after genesis.
+fn first_signed_block_5min_after_genesis(
     conn: &mut db::Connection,
     genesis_time: &chrono::NaiveDateTime,
     player_id: &str,
-) -> anyhow::Result<bool> {
+) -> anyhow::Result<Option<BlockDb>> {
     use diesel::prelude::*;
     use schema::blocks;
     use schema::commits;
@@ -587,12 +998,6 @@ fn signed_block_5min_after_genesis(
 
     let five_mins_after_genesis = *genesis_time + chrono::Duration::minutes(5);
 
-    let eligible_pilot_tm_addrs = blocks::table
-        .inner_join(commits::table)
-        .filter(blocks::dsl::included_at.le(five_mins_after_genesis))
-        .group_by(commits::dsl::address)
-        .select(commits::dsl::address);
-
     let validator_addr_with_same_player_id = players::table
         .filter(
             players::dsl::namada_validator_address
@@ -602,57 +1007,72 @@ fn signed_block_5min_after_genesis(
         )
         .select(players::dsl::namada_validator_address);
 
-    let eligible_pilot_nam_addrs: bool = {
-        let eligible_pilot_nam_addrs = tm_addresses::table
-            .group_by(tm_addresses::dsl::validator_namada_address)
-            .filter(
-                tm_addresses::dsl::validator_namada_address
-                    .nullable()
-                    .eq_any(validator_addr_with_same_player_id)
-                    .and(tm_addresses::dsl::tm_address.eq_any(eligible_pilot_tm_addrs)),
+    let tm_addrs_with_same_player_id = tm_addresses::table
+        .filter(
+            tm_addresses::dsl::validator_namada_address
+                .nullable()
+                .eq_any(validator_addr_with_same_player_id),
+        )
+        .select(tm_addresses::dsl::tm_address);
+
+    let first_signed_block = blocks::table
+        .inner_join(commits::table)
+        .filter(
+            blocks::dsl::included_at
+                .le(five_mins_after_genesis)
+                .and(commits::dsl::address.eq_any(tm_addrs_with_same_player_id)),
+        )
+        .order(blocks::dsl::height.asc())
+        .select(BlockDb::as_select())
+        .first(conn)
+        .optional()
+        .with_context(|| {
+            format!(
+                "Failed to check if pilot with id {player_id} signed \
+                 blocks up to 5 mins after genesis time"
             )
-            .select(tm_addresses::dsl::validator_namada_address);
-
-        eligible_pilot_nam_addrs
-            .exists_inner()
-            .get(conn)
-            .with_context(|| {
-                format!(
-                    "Failed to check if pilot with id {player_id} signed \
-                     blocks up to 5 mins after genesis time"
-                )
-            })?
-    };
+        })?;
 
     tracing::info!(
         %player_id,
-        eligible_pilot_nam_addrs,
+        first_signed_height = first_signed_block.as_ref().map(|block| block.height),
         "Checking if player signed blocks up to 5mins after genesis"
     );
 
-    Ok(eligible_pilot_nam_addrs)
+    Ok(first_signed_block)
 }
 
 fn mark_task_completed_from_tx(
     conn: &mut db::Connection,
     cx: &Context,
+    pending_metrics: &mut PendingMetrics,
     input: Transaction<PlayerId>,
-) -> anyhow::Result<()> {
+) -> anyhow::Result<TaskDecision> {
     use diesel::result::DatabaseErrorKind;
     use diesel::result::Error;
     use diesel::RunQueryDsl;
 
     let tx_id = input.hash.clone();
 
-    let Some(task_insertion) = compute_insertable_task_from_tx(conn, cx, input)? else {
-        tracing::debug!(
-            ?tx_id,
-            "No task to be inserted in database from given tx input"
-        );
-        return Ok(());
+    let InsertableTask {
+        classified,
+        insertion: task_insertion,
+    } = match compute_insertable_task_from_tx(conn, cx, input)? {
+        Ok(insertable) => insertable,
+        Err(decision) => {
+            tracing::debug!(
+                ?tx_id,
+                ?decision,
+                "No task to be inserted in database from given tx input"
+            );
+            return Ok(decision);
+        }
     };
 
     let task_insertion_debug = format!("{task_insertion:?}");
+    let task_type = task_insertion
+        .as_ref()
+        .map_either(|unidentified| unidentified.tx_kind, |task| task.task);
 
     let affected_rows = task_insertion
         .either_with(
@@ -671,7 +1091,7 @@ fn mark_task_completed_from_tx(
 
                 diesel::insert_into(tasks)
                     .values(&insertable_task)
-                    .on_conflict((player_id, task))
+                    .on_conflict((player_id, task, upgrade))
                     .do_nothing()
                     .execute(conn)
             },
@@ -693,12 +1113,295 @@ fn mark_task_completed_from_tx(
 
     if affected_rows == 0 {
         tracing::debug!(?tx_id, "Task already in database, skipping insertion");
+        Ok(TaskDecision::AlreadyCompleted {
+            task: classified.decided(),
+            completed_by_tx: None,
+        })
     } else {
+        pending_metrics.inc_tasks_inserted(task_type.as_ref());
         tracing::info!(
             task = ?task_insertion_debug,
             "Task completed - tx task"
         );
+        Ok(TaskDecision::Credited(classified.decided()))
     }
+}
 
-    Ok(())
+/// Re-evaluate all processed transactions, and revoke the tasks that
+/// were only ever credited by transactions whose exit status does not
+/// allow them to complete a task. Returns the number of revoked tasks.
+pub fn revoke_tasks_from_failed_txs(
+    conn: &mut db::Connection,
+    cx: &Context,
+) -> anyhow::Result<usize> {
+    let Some(last_processed_height) = last_state::read_last_processed_tasks_block(conn)? else {
+        tracing::info!("No transactions have been processed yet, nothing to revoke");
+        return Ok(0);
+    };
+
+    let mut credited = HashSet::new();
+    let mut credited_by_failed_txs = HashSet::new();
+
+    let mut starting_height = 1;
+    while starting_height <= last_processed_height {
+        let ending_height = (starting_height + MAX_BLOCKS_PER_BATCH).min(last_processed_height);
+
+        // NB: the metrics of rescanned transactions are discarded, since
+        // they were recorded when the transactions were first processed
+        process_transactions_in_range(
+            conn,
+            cx.memo_parser(),
+            &mut PendingMetrics::default(),
+            starting_height,
+            ending_height,
+            |conn, _pending_metrics, transaction| {
+                let tx_status = transaction.status.clone();
+                let Ok(ClassifiedTask {
+                    player_id,
+                    task_type,
+                    ..
+                }) = classify_task_from_tx(conn, cx, transaction)?
+                else {
+                    return Ok(());
+                };
+                let credited_on = CreditedOnExitStatus::check(
+                    task_type.as_ref().map_right(|(task, _upgrade)| task),
+                );
+                if credited_on.allows(&tx_status) {
+                    credited.insert((player_id, task_type));
+                } else {
+                    credited_by_failed_txs.insert((player_id, task_type));
+                }
+                Ok(())
+            },
+        )?;
+
+        starting_height = ending_height + 1;
+    }
+
+    let mut revoked_tasks = 0;
+
+    for (player_id, task_type) in credited_by_failed_txs.difference(&credited) {
+        use diesel::dsl::{exists, not};
+        use diesel::prelude::*;
+        use schema::manual_tasks;
+        use schema::tasks;
+        use schema::unidentified_tasks;
+
+        let affected_rows = match task_type {
+            Left(tx_kind) => diesel::delete(
+                unidentified_tasks::table.filter(
+                    unidentified_tasks::dsl::player_id
+                        .eq(player_id)
+                        .and(unidentified_tasks::dsl::tx_kind.eq(tx_kind)),
+                ),
+            )
+            .execute(conn),
+            Right((task, upgrade)) => diesel::delete(
+                tasks::table.filter(
+                    tasks::dsl::player_id
+                        .eq(player_id)
+                        .and(tasks::dsl::task.eq(task))
+                        .and(tasks::dsl::upgrade.eq(upgrade))
+                        .and(not(exists(
+                            manual_tasks::table.filter(
+                                manual_tasks::dsl::player_id
+                                    .eq(player_id)
+                                    .and(manual_tasks::dsl::task.eq(task)),
+                            ),
+                        ))),
+                ),
+            )
+            .execute(conn),
+        }
+        .with_context(|| format!("Failed to revoke task {task_type:?} of {player_id}"))?;
+
+        if affected_rows > 0 {
+            tracing::info!(
+                player_id,
+                ?task_type,
+                "Revoked task credited by a failed transaction"
+            );
+            revoked_tasks += affected_rows;
+        }
+    }
+
+    tracing::info!(
+        revoked_tasks,
+        "Finished revoking tasks credited by failed txs"
+    );
+
+    Ok(revoked_tasks)
+}
+
+/// Record the block and transaction that completed the tasks credited
+/// before completions were recorded, and return the number of tasks
+/// updated.
+///
+/// Transactions are replayed in the order they were included, so each
+/// task is attributed to the first transaction that completed it.
+pub fn backfill_task_completions(conn: &mut db::Connection, cx: &Context) -> anyhow::Result<usize> {
+    let Some(last_processed_height) = last_state::read_last_processed_tasks_block(conn)? else {
+        tracing::info!("No transactions have been processed yet, nothing to backfill");
+        return Ok(0);
+    };
+
+    let mut backfilled_tasks = 0;
+
+    let mut starting_height = 1;
+    while starting_height <= last_processed_height {
+        let ending_height = (starting_height + MAX_BLOCKS_PER_BATCH).min(last_processed_height);
+
+        // NB: the metrics of rescanned transactions are discarded, since
+        // they were recorded when the transactions were first processed
+        process_transactions_in_range(
+            conn,
+            cx.memo_parser(),
+            &mut PendingMetrics::default(),
+            starting_height,
+            ending_height,
+            |conn, _pending_metrics, transaction| {
+                if let Ok(InsertableTask { insertion, .. }) =
+                    compute_insertable_task_from_tx(conn, cx, transaction)?
+                {
+                    backfilled_tasks += backfill_task_completion(conn, &insertion)?;
+                }
+                Ok(())
+            },
+        )?;
+
+        starting_height = ending_height + 1;
+    }
+
+    backfilled_tasks += backfill_pilot_task_completions(conn, cx)?;
+
+    tracing::info!(backfilled_tasks, "Finished backfilling task completions");
+
+    Ok(backfilled_tasks)
+}
+
+/// Record the completion of a task, unless it is already known.
+fn backfill_task_completion(
+    conn: &mut db::Connection,
+    insertion: &Either<UnidentifiedTaskInsertDb, TaskInsertDb>,
+) -> anyhow::Result<usize> {
+    use diesel::prelude::*;
+    use schema::tasks;
+    use schema::unidentified_tasks;
+
+    match insertion {
+        Left(task) => diesel::update(
+            unidentified_tasks::table.filter(
+                unidentified_tasks::dsl::player_id
+                    .eq(&task.player_id)
+                    .and(unidentified_tasks::dsl::tx_kind.eq(&task.tx_kind))
+                    .and(unidentified_tasks::dsl::completed_at_height.is_null()),
+            ),
+        )
+        .set((
+            unidentified_tasks::dsl::completed_at_height.eq(task.completed_at_height),
+            unidentified_tasks::dsl::completed_at.eq(task.completed_at),
+            unidentified_tasks::dsl::completed_by_tx.eq(&task.completed_by_tx),
+        ))
+        .execute(conn)
+        .with_context(|| {
+            format!(
+                "Failed to backfill the completion of unidentified task {:?} of {}",
+                task.tx_kind, task.player_id
+            )
+        }),
+        Right(task) => diesel::update(
+            tasks::table.filter(
+                tasks::dsl::player_id
+                    .eq(&task.player_id)
+                    .and(tasks::dsl::task.eq(&task.task))
+                    .and(tasks::dsl::upgrade.eq(&task.upgrade))
+                    .and(tasks::dsl::completed_at_height.is_null()),
+            ),
+        )
+        .set((
+            tasks::dsl::completed_at_height.eq(task.completed_at_height),
+            tasks::dsl::completed_at.eq(task.completed_at),
+            tasks::dsl::completed_by_tx.eq(&task.completed_by_tx),
+        ))
+        .execute(conn)
+        .with_context(|| {
+            format!(
+                "Failed to backfill the completion of task {:?} of {}",
+                task.task, task.player_id
+            )
+        }),
+    }
+}
+
+/// Record the block that evidences the completion of pilot tasks,
+/// for those credited before completions were recorded.
+fn backfill_pilot_task_completions(
+    conn: &mut db::Connection,
+    cx: &Context,
+) -> anyhow::Result<usize> {
+    use diesel::prelude::*;
+    use schema::tasks;
+
+    let genesis_time = read_genesis_time(conn, cx)?;
+    let pilot_tasks = tasks::table
+        .filter(
+            tasks::dsl::task
+                .eq_any([
+                    TaskTypeDb::StartNode5MinFromGenesis,
+                    TaskTypeDb::SignFirstBlockOfUpgrade,
+                    TaskTypeDb::InValidatorSetFor1Epoch,
+                ])
+                .and(tasks::dsl::completed_at_height.is_null()),
+        )
+        .select((tasks::dsl::player_id, tasks::dsl::task, tasks::dsl::upgrade))
+        .load::<(String, TaskTypeDb, String)>(conn)
+        .context("Failed to query pilot tasks without a recorded completion")?;
+
+    let mut backfilled_tasks = 0;
+
+    for (player_id, task, upgrade) in pilot_tasks {
+        let block = match task {
+            TaskTypeDb::StartNode5MinFromGenesis => match &genesis_time {
+                Some(genesis_time) => {
+                    first_signed_block_5min_after_genesis(conn, genesis_time, &player_id)?
+                }
+                None => None,
+            },
+            TaskTypeDb::SignFirstBlockOfUpgrade => match cx
+                .campaign()
+                .upgrades()
+                .iter()
+                .find(|campaign_upgrade| campaign_upgrade.name == upgrade)
+            {
+                Some(campaign_upgrade) => {
+                    signed_first_block_of_upgrade(conn, campaign_upgrade.epoch as i32, &player_id)?
+                }
+                None => None,
+            },
+            _ => first_signed_block(conn, &player_id)?,
+        };
+        let Some(block) = block else {
+            tracing::warn!(
+                player_id,
+                ?task,
+                upgrade,
+                "No block evidences the completion of a pilot task, leaving it unknown"
+            );
+            continue;
+        };
+        backfilled_tasks += backfill_task_completion(
+            conn,
+            &Right(TaskInsertDb {
+                task,
+                player_id,
+                upgrade,
+                completed_at_height: Some(block.height),
+                completed_at: Some(block.included_at),
+                completed_by_tx: None,
+            }),
+        )?;
+    }
+
+    Ok(backfilled_tasks)
 }
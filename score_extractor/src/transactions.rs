@@ -1,25 +1,30 @@
 use anyhow::Context;
+use serde::Serialize;
+use shared::memo::MemoParser;
 use shared::orm::schema;
+use shared::orm::tasks::{TaskDb, UnidentifiedTaskDb};
 use shared::orm::transaction::TransactionDb;
-use shared::transaction::{RawMemo, Transaction};
+use shared::transaction::{RawMemo, Transaction, TransactionJson};
 
 use crate::db;
 use crate::last_state;
+use crate::memos;
+use crate::metrics::PendingMetrics;
+use crate::players::PlayerId;
+
+/// Maximum number of blocks whose transactions are processed in a single batch.
+pub const MAX_BLOCKS_PER_BATCH: i32 = 1000;
 
 /// Processes a batch of transactions and returns the next height to process.
-pub fn process_last_transactions<F, M>(
+pub fn process_last_transactions<F>(
     conn: &mut db::Connection,
-    mut process: F,
+    memo_parser: &MemoParser,
+    pending_metrics: &mut PendingMetrics,
+    process: F,
 ) -> anyhow::Result<Option<i32>>
 where
-    F: FnMut(&mut db::Connection, Transaction<M>) -> anyhow::Result<()>,
-    M: TryFrom<RawMemo, Error = anyhow::Error>,
+    F: FnMut(&mut db::Connection, &mut PendingMetrics, Transaction<PlayerId>) -> anyhow::Result<()>,
 {
-    use diesel::connection::DefaultLoadingMode;
-    use diesel::prelude::*;
-    use schema::blocks;
-    use schema::transactions;
-
     let Some((starting_height, mut ending_height)) =
         last_state::compute_task_heights_to_process(conn)?
     else {
@@ -27,19 +32,51 @@ where
         return Ok(None);
     };
 
-    if ending_height - starting_height > 1000 {
-        ending_height = starting_height + 1000
+    if ending_height - starting_height > MAX_BLOCKS_PER_BATCH {
+        ending_height = starting_height + MAX_BLOCKS_PER_BATCH
     }
 
-    let blocks_ids_range = blocks::table
+    let processed_txs = process_transactions_in_range(
+        conn,
+        memo_parser,
+        pending_metrics,
+        starting_height,
+        ending_height,
+        process,
+    )?;
+    pending_metrics.observe_transactions_per_batch(processed_txs);
+
+    Ok(Some(ending_height))
+}
+
+/// Processes all transactions included in blocks within the given
+/// (inclusive) height range, and returns the number of processed txs.
+pub fn process_transactions_in_range<F>(
+    conn: &mut db::Connection,
+    memo_parser: &MemoParser,
+    pending_metrics: &mut PendingMetrics,
+    starting_height: i32,
+    ending_height: i32,
+    mut process: F,
+) -> anyhow::Result<usize>
+where
+    F: FnMut(&mut db::Connection, &mut PendingMetrics, Transaction<PlayerId>) -> anyhow::Result<()>,
+{
+    use diesel::connection::DefaultLoadingMode;
+    use diesel::prelude::*;
+    use schema::blocks;
+    use schema::transactions;
+
+    // NB: process transactions in the order they were included, so
+    // that tasks are credited to the first transaction completing them
+    let transactions_in_range = transactions::table
+        .inner_join(blocks::table)
         .filter(
             blocks::dsl::height
                 .ge(starting_height)
                 .and(blocks::dsl::height.le(ending_height)),
         )
-        .select(blocks::dsl::id);
-    let transactions_in_range = transactions::table
-        .filter(transactions::dsl::block_id.eq_any(blocks_ids_range))
+        .order((blocks::dsl::height, transactions::dsl::index))
         .select(TransactionDb::as_select());
 
     let mut processed_txs_counter = 0;
@@ -58,8 +95,10 @@ where
             let transaction: Transaction<RawMemo> = transaction
                 .context("Failed to deserialize transaction from database")?
                 .into();
-            let transaction: Transaction<M> = transaction.try_parse_memo()?;
-            let result = process(conn, transaction);
+            let transaction =
+                memos::resolve_transaction_memo(conn, memo_parser, pending_metrics, transaction)?;
+            store_transaction_player(conn, &transaction)?;
+            let result = process(conn, pending_metrics, transaction);
             processed_txs_counter += 1;
             if processed_txs_counter % PRINT_STEP == 0 {
                 tracing::info!(
@@ -75,8 +114,113 @@ where
     tracing::info!(
         starting_height,
         ending_height,
+        processed_tx_count = processed_txs_counter,
         "Finished processing all transactions in the given block range"
     );
 
-    Ok(Some(ending_height))
+    Ok(processed_txs_counter)
+}
+
+/// Record the player the memo of a transaction resolved to, so that
+/// the transactions of a player can be looked up without resolving
+/// every memo again.
+fn store_transaction_player(
+    conn: &mut db::Connection,
+    transaction: &Transaction<PlayerId>,
+) -> anyhow::Result<()> {
+    use diesel::prelude::*;
+    use schema::transactions;
+
+    let tx_id = transaction.hash.to_string();
+    let player_id = transaction.memo.as_ref().map(|player_id| &player_id.0);
+
+    diesel::update(transactions::table)
+        .filter(
+            transactions::dsl::id
+                .eq(&tx_id)
+                .and(transactions::dsl::player_id.is_distinct_from(player_id)),
+        )
+        .set(transactions::dsl::player_id.eq(player_id))
+        .execute(conn)
+        .with_context(|| format!("Failed to store the player of transaction {tx_id}"))?;
+
+    Ok(())
+}
+
+/// Transaction stored in the database, along with the tasks it credited.
+#[derive(Debug, Serialize)]
+pub struct InspectedTransaction {
+    pub block_id: String,
+    pub block_height: i32,
+    pub transaction: TransactionJson,
+    pub credited_tasks: Vec<TaskDb>,
+    pub credited_unidentified_tasks: Vec<UnidentifiedTaskDb>,
+}
+
+/// Load the transactions with the given hash, or wrapping an inner
+/// transaction with the given hash, along with the height of their block.
+pub fn load_transactions_with_hash(
+    conn: &mut db::Connection,
+    hash: &str,
+) -> anyhow::Result<Vec<(TransactionDb, i32)>> {
+    use diesel::prelude::*;
+    use schema::blocks;
+    use schema::transactions;
+
+    let hash = hash.to_lowercase();
+    transactions::table
+        .inner_join(blocks::table)
+        .filter(
+            transactions::dsl::id
+                .eq(&hash)
+                .or(transactions::dsl::inner_hash.eq(&hash)),
+        )
+        .order((blocks::dsl::height, transactions::dsl::index))
+        .select((TransactionDb::as_select(), blocks::dsl::height))
+        .load::<(TransactionDb, i32)>(conn)
+        .with_context(|| format!("Failed to query transaction {hash}"))
+}
+
+/// Inspect the contents of the transactions with the given hash,
+/// along with the tasks they credited.
+pub fn inspect_transaction(
+    conn: &mut db::Connection,
+    hash: &str,
+) -> anyhow::Result<Vec<InspectedTransaction>> {
+    use diesel::prelude::*;
+    use schema::tasks;
+    use schema::unidentified_tasks;
+
+    load_transactions_with_hash(conn, hash)?
+        .into_iter()
+        .map(|(stored_tx, block_height)| {
+            let credited_tasks = tasks::table
+                .filter(tasks::dsl::completed_by_tx.eq(&stored_tx.id))
+                .order(tasks::dsl::id)
+                .select(TaskDb::as_select())
+                .load(conn)
+                .with_context(|| format!("Failed to query tasks credited by {}", stored_tx.id))?;
+            let credited_unidentified_tasks = unidentified_tasks::table
+                .filter(unidentified_tasks::dsl::completed_by_tx.eq(&stored_tx.id))
+                .order(unidentified_tasks::dsl::id)
+                .select(UnidentifiedTaskDb::as_select())
+                .load(conn)
+                .with_context(|| {
+                    format!(
+                        "Failed to query unidentified tasks credited by {}",
+                        stored_tx.id
+                    )
+                })?;
+            let block_id = stored_tx.block_id.clone();
+            let transaction: Transaction<RawMemo> = stored_tx.into();
+
+            Ok(InspectedTransaction {
+                block_id,
+                block_height,
+                transaction: transaction.to_json(),
+                credited_tasks,
+                credited_unidentified_tasks,
+            })
+        })
+        .collect()
 }
@@ -1,9 +1,8 @@
 use std::collections::HashSet;
 use std::fmt::Display;
 
-use crate::{block_result::Event, player::PlayerId};
+use crate::{block_result::Event, error::ParseError, player::PlayerId};
 use chrono::DateTime;
-use namada_core::borsh::BorshDeserialize;
 use orm::{
     block::BlockInsertDb,
     commits::CommitInsertDb,
@@ -16,8 +15,12 @@ use orm::{
 use tendermint_rpc::endpoint::block::Response as TendermintBlock;
 
 use super::{
-    block_result::BlockResult, commit::Commit, evidence::EvidenceKind, header::BlockHeader, id::Id,
-    transaction::Transaction,
+    block_result::BlockResult,
+    commit::Commit,
+    evidence::EvidenceKind,
+    header::BlockHeader,
+    id::Id,
+    transaction::{DecodedTx, Transaction},
 };
 
 pub type Epoch = u32;
@@ -101,17 +104,20 @@ impl Block {
         self.epoch = epoch;
     }
 
-    pub fn to_block_db(&self) -> BlockInsertDb {
+    pub fn to_block_db(&self) -> Result<BlockInsertDb, ParseError> {
         let timestamp = DateTime::parse_from_rfc3339(&self.header.timestamp)
-            .unwrap()
+            .map_err(|error| ParseError::InvalidTimestamp {
+                timestamp: self.header.timestamp.clone(),
+                error,
+            })?
             .naive_utc();
-        BlockInsertDb {
+        Ok(BlockInsertDb {
             id: self.hash.to_string(),
             height: self.header.height as i32,
             included_at: timestamp,
             proposer_address: self.header.proposer_address.to_string(),
             epoch: self.epoch as i32,
-        }
+        })
     }
 
     pub fn to_transactions_db(&self) -> Vec<TransactionDb> {
@@ -129,15 +135,10 @@ impl Block {
     }
 
     pub fn get_proposal_ids_by_proposal_vote(&self) -> HashSet<u64> {
-        use namada_governance::VoteProposalData;
-
         self.transactions
             .iter()
-            .filter_map(|transaction| match &transaction.kind {
-                crate::transaction::TransactionKind::ProposalVote(data) => {
-                    let data = VoteProposalData::try_from_slice(data).unwrap();
-                    Some(data.id)
-                }
+            .filter_map(|transaction| match transaction.kind.decode() {
+                Ok(DecodedTx::ProposalVote(data)) => Some(data.id),
                 _ => None,
             })
             .collect()
@@ -147,15 +148,14 @@ impl Block {
         &self,
         proposals_id: Vec<i32>,
     ) -> Vec<GovernanceProposalVoteInsertDb> {
-        use namada_governance::{ProposalVote, VoteProposalData};
+        use namada_governance::ProposalVote;
 
         let mut duplicates = HashSet::new();
 
         self.transactions
             .iter()
-            .filter_map(|transaction| match &transaction.kind {
-                crate::transaction::TransactionKind::ProposalVote(data) => {
-                    let data = VoteProposalData::try_from_slice(data).unwrap();
+            .filter_map(|transaction| match transaction.kind.decode() {
+                Ok(DecodedTx::ProposalVote(data)) => {
                     let PlayerId(player_id) = PlayerId::try_from(transaction.memo.clone()?).ok()?;
 
                     let key = format!("{}-{}", data.voter, data.id);
@@ -190,14 +190,13 @@ impl Block {
         &self,
         mut next_proposal_id: u64,
     ) -> Vec<GovernanceProposalInsertDb> {
-        use namada_governance::storage::proposal::{InitProposalData, ProposalType};
+        use namada_governance::storage::proposal::ProposalType;
 
         self.transactions
             .iter()
             .filter(|transaction| transaction.ok())
-            .filter_map(|transaction| match &transaction.kind {
-                crate::transaction::TransactionKind::InitProposal(data) => {
-                    let data = InitProposalData::try_from_slice(data).unwrap();
+            .filter_map(|transaction| match transaction.kind.decode() {
+                Ok(DecodedTx::InitProposal(data)) => {
                     let current_id = next_proposal_id;
                     next_proposal_id += 1;
 
@@ -227,33 +226,35 @@ impl Block {
             .collect()
     }
 
+    /// Convert the evidences of this block to database rows, with one
+    /// row per misbehaving validator.
     pub fn to_evidence_db(&self) -> Vec<EvidenceInsertDb> {
         self.evidences
             .iter()
-            .map(|evidence| match evidence {
-                EvidenceKind::DuplicateVote(address) => EvidenceInsertDb {
+            .flat_map(|evidence| match evidence {
+                EvidenceKind::DuplicateVote(address) => vec![EvidenceInsertDb {
                     kind: EvidenceKindDb::DuplicateVote,
                     validator_address: address.to_string(),
                     block_id: self.hash.to_string(),
-                },
-                EvidenceKind::LightClientAttack(address) => EvidenceInsertDb {
-                    kind: EvidenceKindDb::LightClientAttack,
-                    validator_address: address.to_string(),
-                    block_id: self.hash.to_string(),
-                },
+                }],
+                EvidenceKind::LightClientAttack(addresses) => addresses
+                    .iter()
+                    .map(|address| EvidenceInsertDb {
+                        kind: EvidenceKindDb::LightClientAttack,
+                        validator_address: address.to_string(),
+                        block_id: self.hash.to_string(),
+                    })
+                    .collect(),
             })
             .collect()
     }
 
     pub fn to_validator_address_db(&self) -> Vec<(String, PlayerUpdateValidatorAddressDb)> {
-        use namada_tx::data::pos::BecomeValidator;
-
         self.transactions
             .iter()
-            .filter_map(|transaction| match &transaction.kind {
-                crate::transaction::TransactionKind::BecomeValidator(bytes) => {
+            .filter_map(|transaction| match transaction.kind.decode() {
+                Ok(DecodedTx::BecomeValidator(data)) => {
                     let memo = PlayerId::try_from(transaction.memo.clone()?).ok()?;
-                    let data = BecomeValidator::try_from_slice(bytes).unwrap();
                     let update = PlayerUpdateValidatorAddressDb {
                         namada_validator_address: Some(data.address.to_string()),
                     };
@@ -264,3 +265,97 @@ impl Block {
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use orm::evidences::EvidenceKindDb;
+
+    use super::*;
+    use crate::evidence::tests::{duplicate_vote, light_client_attack};
+
+    fn block_with_evidences(evidences: &[tendermint::evidence::Evidence]) -> Block {
+        Block {
+            hash: Id::Hash("a1b2c3".to_owned()),
+            evidences: evidences.iter().map(EvidenceKind::from).collect(),
+            ..Block::default()
+        }
+    }
+
+    fn evidence_rows(block: &Block) -> Vec<(EvidenceKindDb, String)> {
+        block
+            .to_evidence_db()
+            .into_iter()
+            .inspect(|row| assert_eq!(row.block_id, "a1b2c3"))
+            .map(|row| (row.kind, row.validator_address))
+            .collect()
+    }
+
+    #[test]
+    fn light_client_attack_is_stored_per_byzantine_validator() {
+        let block = block_with_evidences(&[light_client_attack(&[1, 2])]);
+
+        assert_eq!(
+            evidence_rows(&block),
+            [1, 2]
+                .map(|byte| (
+                    EvidenceKindDb::LightClientAttack,
+                    tendermint::account::Id::new([byte; 20]).to_string()
+                ))
+                .to_vec()
+        );
+    }
+
+    #[test]
+    fn light_client_attack_without_byzantine_validators_stores_nothing() {
+        let block = block_with_evidences(&[light_client_attack(&[])]);
+
+        assert!(evidence_rows(&block).is_empty());
+    }
+
+    #[test]
+    fn duplicate_vote_is_stored_once() {
+        let block = block_with_evidences(&[duplicate_vote(7), light_client_attack(&[1])]);
+
+        assert_eq!(
+            evidence_rows(&block),
+            vec![
+                (
+                    EvidenceKindDb::DuplicateVote,
+                    tendermint::account::Id::new([7; 20]).to_string()
+                ),
+                (
+                    EvidenceKindDb::LightClientAttack,
+                    tendermint::account::Id::new([1; 20]).to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_block_timestamp() {
+        let mut block = Block::default();
+        block.header.timestamp = "2024-03-07 11:09:02".to_owned();
+
+        let Err(error) = block.to_block_db() else {
+            panic!("Expected the block timestamp to be rejected");
+        };
+        assert!(matches!(
+            &error,
+            ParseError::InvalidTimestamp { timestamp, .. } if timestamp == "2024-03-07 11:09:02"
+        ));
+    }
+
+    #[test]
+    fn accepts_rfc3339_block_timestamp() {
+        let mut block = Block::default();
+        block.header.height = 52118;
+        block.header.timestamp = "2024-03-07T11:09:02.123456789Z".to_owned();
+
+        let block_db = block.to_block_db().unwrap();
+        assert_eq!(block_db.height, 52118);
+        assert_eq!(
+            block_db.included_at.to_string(),
+            "2024-03-07 11:09:02.123456789"
+        );
+    }
+}
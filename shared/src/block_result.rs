@@ -1,6 +1,7 @@
 use std::{collections::BTreeMap, str::FromStr};
 use tendermint_rpc::endpoint::block_results::Response as TendermintBlockResultResponse;
 
+use crate::error::ParseError;
 use crate::id::Id;
 
 #[derive(Debug, Clone)]
@@ -27,6 +28,7 @@ pub struct BlockResult {
     pub height: u64,
     pub begin_events: Vec<Event>,
     pub end_events: Vec<Event>,
+    pub malformed_events: Vec<MalformedEvent>,
 }
 
 #[derive(Debug, Clone)]
@@ -35,6 +37,15 @@ pub struct Event {
     pub attributes: TxAttributes,
 }
 
+/// Event whose attributes could not be parsed, skipped with a
+/// warning rather than aborting the processing of its block.
+#[derive(Debug, Clone)]
+pub struct MalformedEvent {
+    pub kind: String,
+    pub attributes: BTreeMap<String, String>,
+    pub error: ParseError,
+}
+
 #[derive(Debug, Clone, Default, Copy)]
 pub enum TxEventStatusCode {
     Ok,
@@ -61,44 +72,82 @@ pub struct TxAttributes {
 }
 
 impl TxAttributes {
-    pub fn deserialize(event_kind: &EventKind, attributes: &BTreeMap<String, String>) -> Self {
+    pub fn deserialize(
+        event_kind: &EventKind,
+        attributes: &BTreeMap<String, String>,
+    ) -> Result<Self, ParseError> {
         match event_kind {
-            EventKind::Unknown => Self::default(),
-            _ => Self {
-                code: attributes
-                    .get("code")
-                    .map(|code| TxEventStatusCode::from(code.as_str()))
-                    .unwrap()
-                    .to_owned(),
-                gas: attributes
-                    .get("gas_used")
-                    .map(|gas| u64::from_str(gas).unwrap())
-                    .unwrap()
-                    .to_owned(),
-                hash: attributes
-                    .get("hash")
-                    .map(|hash| Id::Hash(hash.to_lowercase()))
-                    .unwrap()
-                    .to_owned(),
-                height: attributes
-                    .get("height")
-                    .map(|height| u64::from_str(height).unwrap())
-                    .unwrap()
-                    .to_owned(),
-                info: attributes.get("info").unwrap().to_owned(),
-            },
+            EventKind::Unknown => Ok(Self::default()),
+            _ => Ok(Self {
+                code: TxEventStatusCode::from(required_attribute(attributes, "code")?),
+                gas: u64_attribute(attributes, "gas_used")?,
+                hash: Id::Hash(required_attribute(attributes, "hash")?.to_lowercase()),
+                height: u64_attribute(attributes, "height")?,
+                info: required_attribute(attributes, "info")?.to_owned(),
+            }),
+        }
+    }
+}
+
+fn required_attribute<'attrs>(
+    attributes: &'attrs BTreeMap<String, String>,
+    attribute: &'static str,
+) -> Result<&'attrs str, ParseError> {
+    attributes
+        .get(attribute)
+        .map(String::as_str)
+        .ok_or(ParseError::MissingAttribute { attribute })
+}
+
+fn u64_attribute(
+    attributes: &BTreeMap<String, String>,
+    attribute: &'static str,
+) -> Result<u64, ParseError> {
+    let value = required_attribute(attributes, attribute)?;
+    u64::from_str(value).map_err(|error| ParseError::InvalidAttribute {
+        attribute,
+        value: value.to_owned(),
+        error,
+    })
+}
+
+/// Parse a block event, or set it aside if its attributes are malformed.
+fn parse_event(
+    height: u64,
+    kind: &String,
+    raw_attributes: BTreeMap<String, String>,
+) -> Result<Event, MalformedEvent> {
+    let event_kind = EventKind::from(kind);
+    match TxAttributes::deserialize(&event_kind, &raw_attributes) {
+        Ok(attributes) => Ok(Event {
+            kind: event_kind,
+            attributes,
+        }),
+        Err(error) => {
+            tracing::warn!(
+                height,
+                kind,
+                reason = %error,
+                "Skipping malformed block event"
+            );
+            Err(MalformedEvent {
+                kind: kind.to_owned(),
+                attributes: raw_attributes,
+                error,
+            })
         }
     }
 }
 
 impl From<TendermintBlockResultResponse> for BlockResult {
     fn from(value: TendermintBlockResultResponse) -> Self {
+        let height = value.height.value();
+        let mut malformed_events = vec![];
         let begin_events = value
             .begin_block_events
             .unwrap_or_default()
             .iter()
-            .map(|event| {
-                let kind = EventKind::from(&event.kind);
+            .filter_map(|event| {
                 let raw_attributes =
                     event
                         .attributes
@@ -107,16 +156,16 @@ impl From<TendermintBlockResultResponse> for BlockResult {
                             acc.insert(attribute.key.clone(), attribute.value.clone());
                             acc
                         });
-                let attributes = TxAttributes::deserialize(&kind, &raw_attributes);
-                Event { kind, attributes }
+                parse_event(height, &event.kind, raw_attributes)
+                    .map_err(|malformed| malformed_events.push(malformed))
+                    .ok()
             })
             .collect::<Vec<Event>>();
         let end_events = value
             .end_block_events
             .unwrap_or_default()
             .iter()
-            .map(|event| {
-                let kind = EventKind::from(&event.kind);
+            .filter_map(|event| {
                 let raw_attributes =
                     event
                         .attributes
@@ -125,14 +174,16 @@ impl From<TendermintBlockResultResponse> for BlockResult {
                             acc.insert(attribute.key.clone(), attribute.value.clone());
                             acc
                         });
-                let attributes = TxAttributes::deserialize(&kind, &raw_attributes);
-                Event { kind, attributes }
+                parse_event(height, &event.kind, raw_attributes)
+                    .map_err(|malformed| malformed_events.push(malformed))
+                    .ok()
             })
             .collect::<Vec<Event>>();
         Self {
-            height: value.height.value(),
+            height,
             begin_events,
             end_events,
+            malformed_events,
         }
     }
 }
@@ -151,3 +202,124 @@ impl BlockResult {
             .map(|event| event.attributes.clone())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::*;
+
+    const APPLIED_TX_HASH: &str =
+        "d3a0f57c2b1e94a6c8d7e5b3f1a2c4e6b8d0f2a4c6e8b1d3f5a7c9e2b4d6f8a1";
+
+    /// Synthetic `/block_results` response, laid out like those of a
+    /// Namada node. It should be swapped for an actual response, e.g.
+    /// `curl "$RPC/block_results?height=52118" | jq .result`, with the
+    /// expectations of `parses_block_results` updated to match it; the
+    /// malformed cases are derived from whatever this returns.
+    fn block_results() -> Value {
+        serde_json::from_str(include_str!("../fixtures/block_results.synthetic.json")).unwrap()
+    }
+
+    fn parse(block_results: Value) -> BlockResult {
+        let response: TendermintBlockResultResponse =
+            serde_json::from_value(block_results).unwrap();
+        BlockResult::from(response)
+    }
+
+    /// Replace the value of an attribute of the applied event.
+    fn with_applied_attribute(attribute: &str, value: Option<&str>) -> Value {
+        let mut block_results = block_results();
+        let attributes = block_results["end_block_events"][1]["attributes"]
+            .as_array_mut()
+            .unwrap();
+        let position = attributes
+            .iter()
+            .position(|attr| attr["key"] == attribute)
+            .unwrap();
+        match value {
+            Some(value) => attributes[position]["value"] = value.into(),
+            None => {
+                attributes.remove(position);
+            }
+        }
+        block_results
+    }
+
+    fn single_malformed_event(block_result: &BlockResult) -> &MalformedEvent {
+        assert_eq!(block_result.malformed_events.len(), 1);
+        let malformed = &block_result.malformed_events[0];
+        assert_eq!(malformed.kind, "applied");
+        malformed
+    }
+
+    /// The accepted and proposal events still parse next to a
+    /// malformed applied event.
+    fn assert_other_events_parsed(block_result: &BlockResult) {
+        assert_eq!(block_result.end_events.len(), 2);
+        assert!(matches!(
+            block_result.end_events[0].kind,
+            EventKind::Accepted
+        ));
+        assert!(matches!(
+            block_result.end_events[1].kind,
+            EventKind::Unknown
+        ));
+    }
+
+    #[test]
+    fn parses_block_results() {
+        let block_result = parse(block_results());
+
+        assert_eq!(block_result.height, 52118);
+        assert!(block_result.begin_events.is_empty());
+        assert_eq!(block_result.end_events.len(), 3);
+        assert!(block_result.malformed_events.is_empty());
+
+        let applied = block_result
+            .find_tx_hash_result(&Id::Hash(APPLIED_TX_HASH.to_owned()))
+            .unwrap();
+        assert!(matches!(applied.code, TxEventStatusCode::Ok));
+        assert_eq!(applied.gas, 34612);
+        assert_eq!(applied.height, 52118);
+        assert_eq!(applied.info, "Transaction is valid. Gas used: 34612;");
+    }
+
+    #[test]
+    fn skips_event_without_code() {
+        let block_result = parse(with_applied_attribute("code", None));
+
+        let malformed = single_malformed_event(&block_result);
+        assert!(matches!(
+            malformed.error,
+            ParseError::MissingAttribute { attribute: "code" }
+        ));
+        assert!(!malformed.attributes.contains_key("code"));
+        assert_eq!(malformed.attributes["hash"].to_lowercase(), APPLIED_TX_HASH);
+        assert_other_events_parsed(&block_result);
+    }
+
+    #[test]
+    fn skips_event_with_non_numeric_gas_used() {
+        let block_result = parse(with_applied_attribute("gas_used", Some("34612.5")));
+
+        let malformed = single_malformed_event(&block_result);
+        assert!(matches!(
+            &malformed.error,
+            ParseError::InvalidAttribute { attribute: "gas_used", value, .. } if value == "34612.5"
+        ));
+        assert_other_events_parsed(&block_result);
+    }
+
+    #[test]
+    fn skips_event_with_non_numeric_height() {
+        let block_result = parse(with_applied_attribute("height", Some("")));
+
+        let malformed = single_malformed_event(&block_result);
+        assert!(matches!(
+            &malformed.error,
+            ParseError::InvalidAttribute { attribute: "height", value, .. } if value.is_empty()
+        ));
+        assert_other_events_parsed(&block_result);
+    }
+}
@@ -0,0 +1,66 @@
+use std::num::ParseIntError;
+
+/// Errors raised while converting CometBFT data into our own types.
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum ParseError {
+    #[error("Missing event attribute {attribute}")]
+    MissingAttribute { attribute: &'static str },
+    #[error("Invalid value {value:?} of event attribute {attribute}: {error}")]
+    InvalidAttribute {
+        attribute: &'static str,
+        value: String,
+        error: ParseIntError,
+    },
+    #[error("Invalid block timestamp {timestamp:?}: {error}")]
+    InvalidTimestamp {
+        timestamp: String,
+        error: chrono::ParseError,
+    },
+}
+
+/// Errors raised while decoding the data of a transaction.
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum DecodeError {
+    #[error("{kind} transactions carry no data to decode")]
+    NoData { kind: String },
+    #[error("Failed to decode the data of a {kind} transaction: {error}")]
+    InvalidData { kind: String, error: String },
+}
+
+/// Errors raised while parsing the memo of a transaction.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum MemoError {
+    #[error("Memo is not UTF-8 text")]
+    NotUtf8,
+    #[error("Memo is empty")]
+    Empty,
+    #[error("Memo matches none of the accepted formats")]
+    UnknownFormat,
+    #[error("Invalid public key {value:?} in {format} memo: {error}")]
+    InvalidPublicKey {
+        format: &'static str,
+        value: String,
+        error: String,
+    },
+    #[error("Invalid signed claim memo: {error}")]
+    InvalidSignedClaim { error: String },
+    #[error("Address {address} is not an implicit address")]
+    NotImplicitAddress { address: String },
+    #[error("No player is registered with address {address}")]
+    UnknownAddress { address: String },
+}
+
+impl MemoError {
+    /// Short name of the reason why the memo failed to parse.
+    pub fn reason(&self) -> &'static str {
+        match self {
+            Self::NotUtf8 => "not_utf8",
+            Self::Empty => "empty",
+            Self::UnknownFormat => "unknown_format",
+            Self::InvalidPublicKey { .. } => "invalid_public_key",
+            Self::InvalidSignedClaim { .. } => "invalid_signed_claim",
+            Self::NotImplicitAddress { .. } => "not_implicit_address",
+            Self::UnknownAddress { .. } => "unknown_address",
+        }
+    }
+}
@@ -3,7 +3,9 @@ use tendermint::evidence::Evidence as TendermintEvidence;
 #[derive(Debug, Clone)]
 pub enum EvidenceKind {
     DuplicateVote(String),
-    LightClientAttack(String),
+    /// Light client attack, along with the addresses of the
+    /// byzantine validators that took part in it.
+    LightClientAttack(Vec<String>),
 }
 
 impl From<&TendermintEvidence> for EvidenceKind {
@@ -12,7 +14,136 @@ impl From<&TendermintEvidence> for EvidenceKind {
             TendermintEvidence::DuplicateVote(evidence) => {
                 Self::DuplicateVote(evidence.vote_a.validator_address.to_string())
             }
-            TendermintEvidence::LightClientAttack(_) => unimplemented!(),
+            TendermintEvidence::LightClientAttack(evidence) => Self::LightClientAttack(
+                evidence
+                    .byzantine_validators
+                    .iter()
+                    .map(|validator| validator.address.to_string())
+                    .collect(),
+            ),
         }
     }
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use tendermint::account::Id as AccountId;
+    use tendermint::block::header::Version;
+    use tendermint::block::signed_header::SignedHeader;
+    use tendermint::block::{Commit, Header, Height, Round};
+    use tendermint::evidence::{
+        ConflictingBlock, DuplicateVoteEvidence, Evidence, LightClientAttackEvidence,
+    };
+    use tendermint::vote::{Power, Type as VoteType, Vote};
+    use tendermint::{validator, AppHash, Hash, PublicKey, Time};
+
+    use super::*;
+
+    fn validator(byte: u8) -> validator::Info {
+        let pub_key = PublicKey::from_raw_ed25519(&[byte; 32]).unwrap();
+        validator::Info {
+            address: AccountId::new([byte; 20]),
+            ..validator::Info::new(pub_key, Power::from(10_u32))
+        }
+    }
+
+    fn vote(byte: u8) -> Vote {
+        Vote {
+            vote_type: VoteType::Precommit,
+            height: Height::from(10_u32),
+            round: Round::default(),
+            block_id: None,
+            timestamp: Some(Time::unix_epoch()),
+            validator_address: AccountId::new([byte; 20]),
+            validator_index: 0_u32.into(),
+            signature: None,
+            extension: vec![],
+            extension_signature: None,
+        }
+    }
+
+    /// Light client attack evidence, conflicting at height 10 with
+    /// the given byzantine validators.
+    pub(crate) fn light_client_attack(byzantine_validators: &[u8]) -> Evidence {
+        let height = Height::from(10_u32);
+        let header = Header {
+            version: Version { block: 11, app: 0 },
+            chain_id: "namada-test.000000000000000000000".parse().unwrap(),
+            height,
+            time: Time::unix_epoch(),
+            last_block_id: None,
+            last_commit_hash: None,
+            data_hash: None,
+            validators_hash: Hash::None,
+            next_validators_hash: Hash::None,
+            consensus_hash: Hash::None,
+            app_hash: AppHash::default(),
+            last_results_hash: None,
+            evidence_hash: None,
+            proposer_address: AccountId::new([0; 20]),
+        };
+        let commit = Commit {
+            height,
+            round: Round::default(),
+            block_id: Default::default(),
+            signatures: vec![],
+        };
+        let byzantine_validators: Vec<_> = byzantine_validators
+            .iter()
+            .copied()
+            .map(validator)
+            .collect();
+        Evidence::LightClientAttack(Box::new(LightClientAttackEvidence {
+            conflicting_block: ConflictingBlock {
+                signed_header: SignedHeader::new(header, commit).unwrap(),
+                validator_set: validator::Set::new(byzantine_validators.clone(), None),
+            },
+            common_height: Height::from(9_u32),
+            byzantine_validators,
+            total_voting_power: Power::from(100_u32),
+            timestamp: Time::unix_epoch(),
+        }))
+    }
+
+    /// Duplicate vote evidence of the given validator.
+    pub(crate) fn duplicate_vote(byte: u8) -> Evidence {
+        Evidence::DuplicateVote(Box::new(
+            DuplicateVoteEvidence::new(vote(byte), vote(byte)).unwrap(),
+        ))
+    }
+
+    #[test]
+    fn light_client_attack_maps_to_byzantine_validators() {
+        let evidence = EvidenceKind::from(&light_client_attack(&[1, 2, 3]));
+
+        let EvidenceKind::LightClientAttack(addresses) = evidence else {
+            panic!("Expected light client attack evidence, got {evidence:?}");
+        };
+        assert_eq!(
+            addresses,
+            [1, 2, 3]
+                .map(|byte| AccountId::new([byte; 20]).to_string())
+                .to_vec()
+        );
+    }
+
+    #[test]
+    fn light_client_attack_without_byzantine_validators() {
+        let evidence = EvidenceKind::from(&light_client_attack(&[]));
+
+        assert!(
+            matches!(&evidence, EvidenceKind::LightClientAttack(addresses) if addresses.is_empty()),
+            "Expected light client attack evidence without validators, got {evidence:?}"
+        );
+    }
+
+    #[test]
+    fn duplicate_vote_maps_to_voter() {
+        let evidence = EvidenceKind::from(&duplicate_vote(7));
+
+        let EvidenceKind::DuplicateVote(address) = evidence else {
+            panic!("Expected duplicate vote evidence, got {evidence:?}");
+        };
+        assert_eq!(address, AccountId::new([7; 20]).to_string());
+    }
+}
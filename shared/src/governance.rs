@@ -1,3 +1,4 @@
+use namada_governance::storage::proposal::ProposalType;
 use orm::{
     governance_proposals::{GovernanceProposalKindDb, GovernanceProposalResultDb},
     governance_votes::GovernanceVoteKindDb,
@@ -35,6 +36,17 @@ pub enum ProposalKind {
     DefaultWithWasm,
 }
 
+impl From<&ProposalType> for ProposalKind {
+    fn from(value: &ProposalType) -> Self {
+        match value {
+            ProposalType::Default(None) => ProposalKind::Default,
+            ProposalType::Default(Some(_)) => ProposalKind::DefaultWithWasm,
+            ProposalType::PGFSteward(_) => ProposalKind::PgfSteward,
+            ProposalType::PGFPayment(_) => ProposalKind::PgfFunding,
+        }
+    }
+}
+
 impl From<&GovernanceProposalKindDb> for ProposalKind {
     fn from(value: &GovernanceProposalKindDb) -> Self {
         match value {
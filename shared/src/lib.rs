@@ -3,10 +3,12 @@ pub mod block_result;
 pub mod checksums;
 pub mod commit;
 pub mod crawler_state;
+pub mod error;
 pub mod evidence;
 pub mod governance;
 pub mod header;
 pub mod id;
+pub mod memo;
 pub mod player;
 pub mod steward;
 pub mod transaction;
@@ -0,0 +1,493 @@
+use std::str::FromStr;
+
+use namada_core::types::address::Address;
+use namada_core::types::key::{common, SigScheme};
+use serde::Deserialize;
+
+use crate::error::MemoError;
+use crate::player::PlayerId;
+use crate::transaction::RawMemo;
+
+/// Prefixes players may write before their public key in a memo,
+/// matched regardless of case.
+pub const PLAYER_ID_PREFIXES: [&str; 4] = ["player_id:", "player:", "pk:", "id:"];
+
+/// Human readable part of the public keys of players.
+const PUBLIC_KEY_HRP: &str = "tpknam";
+
+/// Player referred to by a memo.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MemoPlayer {
+    /// Id of the player, i.e. their public key.
+    PlayerId(PlayerId),
+    /// Implicit address of the player, to be looked up among the
+    /// addresses players registered with.
+    ImplicitAddress(String),
+}
+
+/// Memo parsed by a [`MemoParser`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedMemo {
+    /// Name of the format the memo was written in.
+    pub format: &'static str,
+    pub player: MemoPlayer,
+}
+
+/// Format of memo accepted by a [`MemoParser`].
+pub trait MemoFormat: Send + Sync {
+    /// Name of the format, e.g. to label statistics.
+    fn name(&self) -> &'static str;
+
+    /// Parse the player referred to by a normalized memo, or return
+    /// `None` if the memo is not written in this format.
+    fn parse(&self, memo: &str) -> Option<Result<MemoPlayer, MemoError>>;
+}
+
+/// Memo holding the public key of the player, e.g. `tpknam1...`.
+pub struct PublicKeyMemo;
+
+impl MemoFormat for PublicKeyMemo {
+    fn name(&self) -> &'static str {
+        "public_key"
+    }
+
+    fn parse(&self, memo: &str) -> Option<Result<MemoPlayer, MemoError>> {
+        // NB: memos that do not even look like a public key may be
+        // written in another format
+        let looks_like_public_key = memo
+            .get(..PUBLIC_KEY_HRP.len())
+            .is_some_and(|hrp| hrp.eq_ignore_ascii_case(PUBLIC_KEY_HRP));
+        if !looks_like_public_key {
+            return None;
+        }
+        Some(
+            parse_public_key(self.name(), memo)
+                .map(|(player_id, _public_key)| MemoPlayer::PlayerId(player_id)),
+        )
+    }
+}
+
+/// Memo holding the public key of the player after one of the
+/// [`PLAYER_ID_PREFIXES`], e.g. `player: tpknam1...`.
+pub struct PrefixedPublicKeyMemo;
+
+impl MemoFormat for PrefixedPublicKeyMemo {
+    fn name(&self) -> &'static str {
+        "prefixed_public_key"
+    }
+
+    fn parse(&self, memo: &str) -> Option<Result<MemoPlayer, MemoError>> {
+        let public_key = PLAYER_ID_PREFIXES.iter().find_map(|prefix| {
+            memo.get(..prefix.len())
+                .filter(|memo_prefix| memo_prefix.eq_ignore_ascii_case(prefix))
+                .map(|_| memo[prefix.len()..].trim_start())
+        })?;
+        Some(
+            parse_public_key(self.name(), public_key)
+                .map(|(player_id, _public_key)| MemoPlayer::PlayerId(player_id)),
+        )
+    }
+}
+
+/// Memo holding a JSON claim signed by the player, e.g.
+/// `{"player_id": "tpknam1...", "signature": "signam1..."}`.
+///
+/// The signature must be made with the key of the player over
+/// their id, proving that they own it.
+pub struct SignedClaimMemo;
+
+#[derive(Deserialize)]
+struct SignedClaim {
+    player_id: String,
+    signature: String,
+}
+
+impl MemoFormat for SignedClaimMemo {
+    fn name(&self) -> &'static str {
+        "signed_claim"
+    }
+
+    fn parse(&self, memo: &str) -> Option<Result<MemoPlayer, MemoError>> {
+        if !memo.starts_with('{') {
+            return None;
+        }
+        Some(parse_signed_claim(self.name(), memo))
+    }
+}
+
+/// Memo holding the implicit address of the player, e.g. `tnam1...`.
+pub struct ImplicitAddressMemo;
+
+impl MemoFormat for ImplicitAddressMemo {
+    fn name(&self) -> &'static str {
+        "implicit_address"
+    }
+
+    fn parse(&self, memo: &str) -> Option<Result<MemoPlayer, MemoError>> {
+        let address = memo.to_lowercase();
+        match Address::from_str(&address).ok()? {
+            Address::Implicit(_) => Some(Ok(MemoPlayer::ImplicitAddress(address))),
+            _ => Some(Err(MemoError::NotImplicitAddress { address })),
+        }
+    }
+}
+
+/// Parser of the memos of transactions, trying each of its formats
+/// in turn.
+pub struct MemoParser {
+    formats: Vec<Box<dyn MemoFormat>>,
+}
+
+impl Default for MemoParser {
+    /// Parser accepting all the formats documented in this module.
+    fn default() -> Self {
+        Self::new(vec![
+            Box::new(SignedClaimMemo),
+            Box::new(PrefixedPublicKeyMemo),
+            Box::new(PublicKeyMemo),
+            Box::new(ImplicitAddressMemo),
+        ])
+    }
+}
+
+impl MemoParser {
+    pub fn new(formats: Vec<Box<dyn MemoFormat>>) -> Self {
+        Self { formats }
+    }
+
+    /// Accept memos in the given format, after the existing ones.
+    pub fn with_format(mut self, format: impl MemoFormat + 'static) -> Self {
+        self.formats.push(Box::new(format));
+        self
+    }
+
+    /// Names of the formats accepted by this parser.
+    pub fn format_names(&self) -> Vec<&'static str> {
+        self.formats.iter().map(|format| format.name()).collect()
+    }
+
+    /// Parse the player referred to by the given memo.
+    pub fn parse(&self, RawMemo(raw_memo): &RawMemo) -> Result<ParsedMemo, MemoError> {
+        let memo = std::str::from_utf8(raw_memo).map_err(|_| MemoError::NotUtf8)?;
+        let memo = normalize(memo);
+        if memo.is_empty() {
+            return Err(MemoError::Empty);
+        }
+        self.formats
+            .iter()
+            .find_map(|format| {
+                let player = format.parse(memo)?;
+                Some(player.map(|player| ParsedMemo {
+                    format: format.name(),
+                    player,
+                }))
+            })
+            .unwrap_or(Err(MemoError::UnknownFormat))
+    }
+}
+
+/// Strip the whitespace, NUL padding and quotes players often
+/// leave around their memo.
+pub fn normalize(memo: &str) -> &str {
+    memo.trim_matches(|c: char| c.is_whitespace() || c == '\0')
+        .trim_matches(|c| matches!(c, '"' | '\'' | '`'))
+        .trim()
+}
+
+fn parse_public_key(
+    format: &'static str,
+    memo: &str,
+) -> Result<(PlayerId, common::PublicKey), MemoError> {
+    let player_id = memo.to_lowercase();
+    let public_key =
+        common::PublicKey::from_str(&player_id).map_err(|err| MemoError::InvalidPublicKey {
+            format,
+            value: memo.to_owned(),
+            error: err.to_string(),
+        })?;
+    Ok((PlayerId(player_id), public_key))
+}
+
+fn parse_signed_claim(format: &'static str, memo: &str) -> Result<MemoPlayer, MemoError> {
+    let claim: SignedClaim =
+        serde_json::from_str(memo).map_err(|err| MemoError::InvalidSignedClaim {
+            error: err.to_string(),
+        })?;
+    let (player_id, public_key) = parse_public_key(format, claim.player_id.trim())?;
+    let signature = common::Signature::from_str(claim.signature.trim()).map_err(|err| {
+        MemoError::InvalidSignedClaim {
+            error: format!("Invalid signature: {err}"),
+        }
+    })?;
+    common::SigScheme::verify_signature_raw(&public_key, player_id.0.as_bytes(), &signature)
+        .map_err(|err| MemoError::InvalidSignedClaim {
+            error: format!("Signature does not match the player id: {err}"),
+        })?;
+    Ok(MemoPlayer::PlayerId(player_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLAYER_PK: &str = "tpknam1qz9g3c7awsylr90a2tdj6096t4ev5ecfhuwegysm7d6gsqd5pah4cgftgzc";
+    const OTHER_PK: &str = "tpknam1qzqnjacw4p73wh6k5d2xds6v0mxvhrv2jx6wudazthmq7ku0exeegfmlveu";
+    /// Signature of [`PLAYER_PK`] over itself.
+    const PLAYER_SIG: &str = "signam1qrc5s57el4ar3ehjsxuemn9gx42yy6vhdkq0pxjyp3gstgu43rqgw5l57j44lxfyc9c4538z2j9cr3qkxq32esdunlrqd5a9yx5zdvsx4kgz5q";
+    /// Signature of [`OTHER_PK`] over [`PLAYER_PK`].
+    const OTHER_SIG_OF_PLAYER: &str = "signam1qzuxcjyy3n5tqv2l0l2ueqt08nd7hlm7zzqehxc43e4rsg82ey2zz3d2uh7xseve5qxvk49s5gfutssl3y6lkpt33jskegrkwp6lquqzqvamkg";
+    /// Signature of [`PLAYER_PK`] over [`OTHER_PK`].
+    const PLAYER_SIG_OF_OTHER: &str = "signam1qzzqnzpf7fttrr7vtrm99s30zfhqvdrgdpsg8x46229r8lcnznxjdu3hytf8xzdmqukqpjp4tk32ygkxwyg0pmfh83lz6jcxaq44axq0xjgsq5";
+    const ESTABLISHED_ADDRESS: &str = "tnam1qxgfw7myv4dh0qna4hq0xdg6lx77fzl7dcem8h7e";
+
+    fn parse(memo: impl Into<Vec<u8>>) -> Result<ParsedMemo, MemoError> {
+        MemoParser::default().parse(&RawMemo(memo.into()))
+    }
+
+    fn player(format: &'static str) -> Result<ParsedMemo, MemoError> {
+        Ok(ParsedMemo {
+            format,
+            player: MemoPlayer::PlayerId(PlayerId(PLAYER_PK.to_owned())),
+        })
+    }
+
+    fn signed_claim(player_id: &str, signature: &str) -> String {
+        format!(r#"{{"player_id": "{player_id}", "signature": "{signature}"}}"#)
+    }
+
+    fn implicit_address() -> String {
+        let public_key = common::PublicKey::from_str(PLAYER_PK).unwrap();
+        Address::from(&public_key).to_string()
+    }
+
+    #[test]
+    fn parses_documented_formats() {
+        let cases = [
+            ("raw public key", PLAYER_PK.to_owned(), "public_key"),
+            (
+                "upper case public key",
+                PLAYER_PK.to_uppercase(),
+                "public_key",
+            ),
+            (
+                "prefixed public key",
+                format!("player_id:{PLAYER_PK}"),
+                "prefixed_public_key",
+            ),
+            (
+                "mixed case prefix",
+                format!("Player: {PLAYER_PK}"),
+                "prefixed_public_key",
+            ),
+            (
+                "upper case prefix",
+                format!("PK:  {PLAYER_PK}"),
+                "prefixed_public_key",
+            ),
+            (
+                "short prefix",
+                format!("id:{PLAYER_PK}"),
+                "prefixed_public_key",
+            ),
+            (
+                "signed claim",
+                signed_claim(PLAYER_PK, PLAYER_SIG),
+                "signed_claim",
+            ),
+            (
+                "padded signed claim",
+                format!(" {}\n", signed_claim(&format!(" {PLAYER_PK} "), PLAYER_SIG)),
+                "signed_claim",
+            ),
+        ];
+
+        for (case, memo, format) in cases {
+            assert_eq!(parse(memo), player(format), "{case}");
+        }
+    }
+
+    #[test]
+    fn strips_padding() {
+        let cases = [
+            ("whitespace", format!(" \t{PLAYER_PK}\r\n")),
+            ("NUL padding", format!("{PLAYER_PK}\0\0\0\0")),
+            ("double quotes", format!("\"{PLAYER_PK}\"")),
+            ("single quotes", format!("'{PLAYER_PK}'")),
+            ("backticks", format!("`{PLAYER_PK}`")),
+            ("quotes and whitespace", format!("\0 \"{PLAYER_PK} \"\n")),
+        ];
+
+        for (case, memo) in cases {
+            assert_eq!(parse(memo), player("public_key"), "{case}");
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_signed_claims() {
+        let cases = [
+            ("malformed JSON", format!(r#"{{"player_id": "{PLAYER_PK}""#)),
+            (
+                "missing signature",
+                format!(r#"{{"player_id": "{PLAYER_PK}"}}"#),
+            ),
+            (
+                "malformed signature",
+                signed_claim(PLAYER_PK, "signam1qqqq"),
+            ),
+            (
+                "signature of another key",
+                signed_claim(PLAYER_PK, OTHER_SIG_OF_PLAYER),
+            ),
+            (
+                "signature over another player id",
+                signed_claim(OTHER_PK, PLAYER_SIG_OF_OTHER),
+            ),
+            (
+                "signature of another claim",
+                signed_claim(OTHER_PK, PLAYER_SIG),
+            ),
+        ];
+
+        for (case, memo) in cases {
+            let result = parse(memo);
+            assert!(
+                matches!(result, Err(MemoError::InvalidSignedClaim { .. })),
+                "{case}: {result:?}"
+            );
+        }
+
+        let result = parse(signed_claim("tpknam1typo", PLAYER_SIG));
+        assert!(
+            matches!(
+                result,
+                Err(MemoError::InvalidPublicKey {
+                    format: "signed_claim",
+                    ..
+                })
+            ),
+            "{result:?}"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_public_keys() {
+        // drop the last character of the checksum
+        let typo = &PLAYER_PK[..PLAYER_PK.len() - 1];
+        let cases = [
+            ("typo", typo.to_owned(), "public_key"),
+            ("upper case typo", typo.to_uppercase(), "public_key"),
+            (
+                "prefixed typo",
+                format!("player: {typo}"),
+                "prefixed_public_key",
+            ),
+            (
+                "prefix without key",
+                "pk:".to_owned(),
+                "prefixed_public_key",
+            ),
+        ];
+
+        for (case, memo, expected_format) in cases {
+            let result = parse(memo);
+            assert!(
+                matches!(
+                    &result,
+                    Err(MemoError::InvalidPublicKey { format, .. }) if *format == expected_format
+                ),
+                "{case}: {result:?}"
+            );
+            assert_eq!(result.unwrap_err().reason(), "invalid_public_key", "{case}");
+        }
+    }
+
+    #[test]
+    fn parses_implicit_addresses() {
+        let address = implicit_address();
+
+        for memo in [
+            address.clone(),
+            address.to_uppercase(),
+            format!(" '{address}'\0"),
+        ] {
+            assert_eq!(
+                parse(memo.clone()),
+                Ok(ParsedMemo {
+                    format: "implicit_address",
+                    player: MemoPlayer::ImplicitAddress(address.clone()),
+                }),
+                "{memo:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_established_addresses() {
+        assert_eq!(
+            parse(ESTABLISHED_ADDRESS),
+            Err(MemoError::NotImplicitAddress {
+                address: ESTABLISHED_ADDRESS.to_owned()
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_empty_and_unknown_memos() {
+        let cases: [(&str, Vec<u8>, MemoError); 6] = [
+            ("empty", vec![], MemoError::Empty),
+            ("whitespace", b" \n\t".to_vec(), MemoError::Empty),
+            ("NUL padding", vec![0; 32], MemoError::Empty),
+            ("quotes", b"\"\"".to_vec(), MemoError::Empty),
+            ("not UTF-8", vec![0xff, 0xfe, 0x00], MemoError::NotUtf8),
+            (
+                "free text",
+                b"hello namada".to_vec(),
+                MemoError::UnknownFormat,
+            ),
+        ];
+
+        for (case, memo, error) in cases {
+            assert_eq!(parse(memo), Err(error), "{case}");
+        }
+    }
+
+    #[test]
+    fn tries_formats_in_order() {
+        struct AnyMemo;
+
+        impl MemoFormat for AnyMemo {
+            fn name(&self) -> &'static str {
+                "any"
+            }
+
+            fn parse(&self, memo: &str) -> Option<Result<MemoPlayer, MemoError>> {
+                Some(Ok(MemoPlayer::PlayerId(PlayerId(memo.to_owned()))))
+            }
+        }
+
+        let parser = MemoParser::default().with_format(AnyMemo);
+        assert_eq!(
+            parser.format_names(),
+            [
+                "signed_claim",
+                "prefixed_public_key",
+                "public_key",
+                "implicit_address",
+                "any"
+            ]
+        );
+        // formats are tried in turn, so the catch-all one only gets
+        // the memos no other format accepts
+        assert_eq!(
+            parser.parse(&RawMemo(PLAYER_PK.into())),
+            player("public_key")
+        );
+        assert_eq!(
+            parser.parse(&RawMemo(b" hello ".to_vec())),
+            Ok(ParsedMemo {
+                format: "any",
+                player: MemoPlayer::PlayerId(PlayerId("hello".to_owned())),
+            })
+        );
+    }
+}
@@ -1,9 +1,11 @@
 use std::fmt;
+use std::sync::OnceLock;
 
-use anyhow::Context;
+use anyhow::anyhow;
 use orm::players::PlayerKindDb;
 
 use crate::id::Id;
+use crate::memo::{MemoParser, MemoPlayer};
 use crate::transaction::RawMemo;
 
 #[derive(Debug, Clone)]
@@ -45,23 +47,27 @@ pub struct PlayerId(pub String);
 impl TryFrom<RawMemo> for PlayerId {
     type Error = anyhow::Error;
 
-    fn try_from(RawMemo(raw_memo): RawMemo) -> Result<Self, Self::Error> {
-        use std::str::FromStr;
-        let originating_player = String::from_utf8(raw_memo).context("Memo is not UTF-8 text")?;
-        namada_core::types::key::common::PublicKey::from_str(&originating_player)
-            .context("Invalid Namada public key")?;
-        Ok(Self(originating_player))
+    fn try_from(raw_memo: RawMemo) -> Result<Self, Self::Error> {
+        Self::try_from(&raw_memo)
     }
 }
 
 impl TryFrom<&RawMemo> for PlayerId {
     type Error = anyhow::Error;
 
+    /// Parse the player id in a memo, with the default [`MemoParser`].
+    ///
+    /// Memos holding addresses are rejected, since mapping them back
+    /// to players requires querying the database.
     fn try_from(raw: &RawMemo) -> Result<Self, Self::Error> {
-        use std::str::FromStr;
-        let originating_player = std::str::from_utf8(&raw.0).context("Memo is not UTF-8 text")?;
-        namada_core::types::key::common::PublicKey::from_str(originating_player)
-            .context("Invalid Namada public key")?;
-        Ok(Self(originating_player.to_owned()))
+        static MEMO_PARSER: OnceLock<MemoParser> = OnceLock::new();
+
+        let parsed = MEMO_PARSER.get_or_init(MemoParser::default).parse(raw)?;
+        match parsed.player {
+            MemoPlayer::PlayerId(player_id) => Ok(player_id),
+            MemoPlayer::ImplicitAddress(address) => Err(anyhow!(
+                "Memo holds the address {address}, which has yet to be mapped to a player"
+            )),
+        }
     }
 }
@@ -1,44 +1,262 @@
 use std::fmt::Display;
+use std::sync::OnceLock;
 
 use namada_core::borsh::BorshDeserialize;
+use namada_core::types::{address::Address, key::common::PublicKey, token::Transfer};
+use namada_governance::{storage::proposal::InitProposalData, VoteProposalData};
+use namada_ibc::IbcMessage;
+use namada_tx::data::{
+    account::{InitAccount, UpdateAccount},
+    pgf::UpdateStewardCommission,
+    pos::{
+        BecomeValidator, Bond, ClaimRewards, CommissionChange, ConsensusKeyChange, MetaDataChange,
+        Redelegation, Unbond, Withdraw,
+    },
+};
 use namada_tx::{data::TxType, Tx as NamadaTx};
 use orm::transaction::{TransactionDb, TransactionExitStatusDb, TransactionKindDb};
+use serde::Serialize;
+use serde_json::json;
 
 use crate::{
     block_result::{BlockResult, TxAttributes, TxEventStatusCode},
     checksums::Checksums,
+    error::DecodeError,
+    governance::ProposalKind,
+    player::PlayerId,
 };
 
 use super::id::Id;
 
+/// Borsh encoded data of a transaction, decoded at most once.
+#[derive(Debug, Clone, Default)]
+pub struct TxData {
+    bytes: Vec<u8>,
+    // NB: boxed, since some Namada payloads are rather large
+    decoded: OnceLock<Result<Box<DecodedTx>, DecodeError>>,
+}
+
+impl From<Vec<u8>> for TxData {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self {
+            bytes,
+            decoded: OnceLock::new(),
+        }
+    }
+}
+
+impl TxData {
+    /// Wrap data that has already been decoded.
+    fn decoded(bytes: Vec<u8>, decoded: DecodedTx) -> Self {
+        Self {
+            bytes,
+            decoded: OnceLock::from(Ok(Box::new(decoded))),
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+/// Data of a transaction, decoded into the matching Namada type.
+#[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)]
+pub enum DecodedTx {
+    /// Transparent or shielded transfer.
+    Transfer(Transfer),
+    Bond(Bond),
+    Redelegation(Redelegation),
+    Unbond(Unbond),
+    Withdraw(Withdraw),
+    ClaimRewards(ClaimRewards),
+    ReactivateValidator(Address),
+    DeactivateValidator(Address),
+    Ibc(IbcMessage),
+    ChangeConsensusKey(ConsensusKeyChange),
+    ChangeCommission(CommissionChange),
+    ChangeMetadata(MetaDataChange),
+    BecomeValidator(BecomeValidator),
+    InitAccount(InitAccount),
+    InitProposal(InitProposalData),
+    ResignSteward(Address),
+    RevealPublicKey(PublicKey),
+    UnjailValidator(Address),
+    UpdateAccount(UpdateAccount),
+    UpdateStewardCommissions(UpdateStewardCommission),
+    ProposalVote(VoteProposalData),
+}
+
+/// Name the kind of an IBC envelope message, e.g. `Client::UpdateClient`,
+/// from the variants its debug representation starts with.
+fn ibc_message_type(envelope: &impl std::fmt::Debug) -> String {
+    format!("{envelope:?}")
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|ident| !ident.is_empty())
+        .take(2)
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+impl DecodedTx {
+    /// Render the fields of the decoded data as JSON.
+    pub fn to_json(&self) -> serde_json::Value {
+        fn to_strings<T: Display>(values: &[T]) -> Vec<String> {
+            values.iter().map(ToString::to_string).collect()
+        }
+
+        match self {
+            Self::Transfer(transfer) => json!({
+                "source": transfer.source.to_string(),
+                "target": transfer.target.to_string(),
+                "token": transfer.token.to_string(),
+                "amount": transfer.amount.to_string(),
+                "key": transfer.key,
+                "shielded": transfer.shielded.map(|hash| hash.to_string()),
+            }),
+            Self::Bond(bond) | Self::Unbond(bond) => json!({
+                "validator": bond.validator.to_string(),
+                "amount": bond.amount.to_string_native(),
+                "source": bond.source.as_ref().map(ToString::to_string),
+            }),
+            Self::Redelegation(redelegation) => json!({
+                "src_validator": redelegation.src_validator.to_string(),
+                "dest_validator": redelegation.dest_validator.to_string(),
+                "owner": redelegation.owner.to_string(),
+                "amount": redelegation.amount.to_string_native(),
+            }),
+            Self::Withdraw(withdraw) | Self::ClaimRewards(withdraw) => json!({
+                "validator": withdraw.validator.to_string(),
+                "source": withdraw.source.as_ref().map(ToString::to_string),
+            }),
+            Self::ReactivateValidator(validator)
+            | Self::DeactivateValidator(validator)
+            | Self::UnjailValidator(validator) => json!({
+                "validator": validator.to_string(),
+            }),
+            Self::Ibc(message) => {
+                let transfer = match message {
+                    IbcMessage::Envelope(envelope) => {
+                        return json!({
+                            "message_type": ibc_message_type(envelope),
+                        });
+                    }
+                    IbcMessage::Transfer(transfer) => transfer,
+                    IbcMessage::ShieldedTransfer(shielded) => &shielded.message,
+                };
+                json!({
+                    "sender": transfer.packet_data.sender.to_string(),
+                    "receiver": transfer.packet_data.receiver.to_string(),
+                    "denom": transfer.packet_data.token.denom.to_string(),
+                    "amount": transfer.packet_data.token.amount.to_string(),
+                    "port": transfer.port_id_on_a.to_string(),
+                    "channel": transfer.chan_id_on_a.to_string(),
+                    "memo": transfer.packet_data.memo.to_string(),
+                })
+            }
+            Self::ChangeConsensusKey(change) => json!({
+                "validator": change.validator.to_string(),
+                "consensus_key": change.consensus_key.to_string(),
+            }),
+            Self::ChangeCommission(change) => json!({
+                "validator": change.validator.to_string(),
+                "new_rate": change.new_rate.to_string(),
+            }),
+            Self::ChangeMetadata(change) => json!({
+                "validator": change.validator.to_string(),
+                "email": change.email,
+                "description": change.description,
+                "website": change.website,
+                "discord_handle": change.discord_handle,
+                "avatar": change.avatar,
+                "commission_rate": change.commission_rate.map(|rate| rate.to_string()),
+            }),
+            Self::BecomeValidator(validator) => json!({
+                "address": validator.address.to_string(),
+                "consensus_key": validator.consensus_key.to_string(),
+                "eth_cold_key": validator.eth_cold_key.to_string(),
+                "eth_hot_key": validator.eth_hot_key.to_string(),
+                "protocol_key": validator.protocol_key.to_string(),
+                "commission_rate": validator.commission_rate.to_string(),
+                "max_commission_rate_change": validator.max_commission_rate_change.to_string(),
+                "email": validator.email,
+                "description": validator.description,
+                "website": validator.website,
+                "discord_handle": validator.discord_handle,
+                "avatar": validator.avatar,
+            }),
+            Self::InitAccount(account) => json!({
+                "public_keys": to_strings(&account.public_keys),
+                "vp_code_hash": account.vp_code_hash.to_string(),
+                "threshold": account.threshold,
+            }),
+            Self::InitProposal(proposal) => json!({
+                "id": proposal.id,
+                "content": proposal.content.to_string(),
+                "author": proposal.author.to_string(),
+                "kind": ProposalKind::from(&proposal.r#type),
+                "voting_start_epoch": proposal.voting_start_epoch.0,
+                "voting_end_epoch": proposal.voting_end_epoch.0,
+                "grace_epoch": proposal.grace_epoch.0,
+            }),
+            Self::ResignSteward(steward) => json!({
+                "steward": steward.to_string(),
+            }),
+            Self::RevealPublicKey(public_key) => json!({
+                "public_key": public_key.to_string(),
+            }),
+            Self::UpdateAccount(account) => json!({
+                "address": account.addr.to_string(),
+                "vp_code_hash": account.vp_code_hash.map(|hash| hash.to_string()),
+                "public_keys": to_strings(&account.public_keys),
+                "threshold": account.threshold,
+            }),
+            Self::UpdateStewardCommissions(update) => json!({
+                "steward": update.steward.to_string(),
+                "commission": update
+                    .commission
+                    .iter()
+                    .map(|(address, rate)| (address.to_string(), rate.to_string()))
+                    .collect::<std::collections::BTreeMap<_, _>>(),
+            }),
+            Self::ProposalVote(vote) => json!({
+                "id": vote.id,
+                "vote": vote.vote.to_string(),
+                "voter": vote.voter.to_string(),
+                "delegations": to_strings(&vote.delegations),
+            }),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum TransactionKind {
     Wrapper,
     Protocol,
-    TransparentTransfer(Vec<u8>),
-    ShieldedTransfer(Vec<u8>),
-    Bond(Vec<u8>),
-    Redelegation(Vec<u8>),
-    Unbond(Vec<u8>),
-    Withdraw(Vec<u8>),
-    ClaimRewards(Vec<u8>),
-    ReactivateValidator(Vec<u8>),
-    DeactivateValidator(Vec<u8>),
-    IbcEnvelop(Vec<u8>),
-    IbcTransparentTransfer(Vec<u8>),
-    IbcShieldedTransfer(Vec<u8>),
-    ChangeConsensusKey(Vec<u8>),
-    ChangeCommission(Vec<u8>),
-    ChangeMetadata(Vec<u8>),
-    BecomeValidator(Vec<u8>),
-    InitAccount(Vec<u8>),
-    InitProposal(Vec<u8>),
-    ResignSteward(Vec<u8>),
-    RevealPublicKey(Vec<u8>),
-    UnjailValidator(Vec<u8>),
-    UpdateAccount(Vec<u8>),
-    UpdateStewardCommissions(Vec<u8>),
-    ProposalVote(Vec<u8>),
+    TransparentTransfer(TxData),
+    ShieldedTransfer(TxData),
+    Bond(TxData),
+    Redelegation(TxData),
+    Unbond(TxData),
+    Withdraw(TxData),
+    ClaimRewards(TxData),
+    ReactivateValidator(TxData),
+    DeactivateValidator(TxData),
+    IbcEnvelop(TxData),
+    IbcTransparentTransfer(TxData),
+    IbcShieldedTransfer(TxData),
+    ChangeConsensusKey(TxData),
+    ChangeCommission(TxData),
+    ChangeMetadata(TxData),
+    BecomeValidator(TxData),
+    InitAccount(TxData),
+    InitProposal(TxData),
+    ResignSteward(TxData),
+    RevealPublicKey(TxData),
+    UnjailValidator(TxData),
+    UpdateAccount(TxData),
+    UpdateStewardCommissions(TxData),
+    ProposalVote(TxData),
     Unknown,
 }
 
@@ -162,25 +380,30 @@ impl TransactionKind {
     pub fn from(tx_kind_name: &str, data: &[u8]) -> Self {
         match tx_kind_name {
             "tx_transfer" => {
-                let transfer_data =
-                    if let Ok(tx) = namada_core::types::token::Transfer::try_from_slice(data) {
-                        tx
-                    } else {
-                        return TransactionKind::Unknown;
-                    };
+                let transfer_data = if let Ok(tx) = Transfer::try_from_slice(data) {
+                    tx
+                } else {
+                    return TransactionKind::Unknown;
+                };
 
                 match transfer_data.shielded {
-                    Some(_) => TransactionKind::ShieldedTransfer(data.to_vec()),
-                    None => TransactionKind::TransparentTransfer(data.to_vec()),
+                    Some(_) => TransactionKind::ShieldedTransfer(TxData::decoded(
+                        data.to_vec(),
+                        DecodedTx::Transfer(transfer_data),
+                    )),
+                    None => TransactionKind::TransparentTransfer(TxData::decoded(
+                        data.to_vec(),
+                        DecodedTx::Transfer(transfer_data),
+                    )),
                 }
             }
-            "tx_bond" => TransactionKind::Bond(data.to_vec()),
-            "tx_redelegation" => TransactionKind::Redelegation(data.to_vec()),
-            "tx_unbond" => TransactionKind::Unbond(data.to_vec()),
-            "tx_withdraw" => TransactionKind::Withdraw(data.to_vec()),
-            "tx_claim_rewards" => TransactionKind::ClaimRewards(data.to_vec()),
-            "tx_reactivate_validator" => TransactionKind::ReactivateValidator(data.to_vec()),
-            "tx_deactivate_validator" => TransactionKind::DeactivateValidator(data.to_vec()),
+            "tx_bond" => TransactionKind::Bond(data.to_vec().into()),
+            "tx_redelegation" => TransactionKind::Redelegation(data.to_vec().into()),
+            "tx_unbond" => TransactionKind::Unbond(data.to_vec().into()),
+            "tx_withdraw" => TransactionKind::Withdraw(data.to_vec().into()),
+            "tx_claim_rewards" => TransactionKind::ClaimRewards(data.to_vec().into()),
+            "tx_reactivate_validator" => TransactionKind::ReactivateValidator(data.to_vec().into()),
+            "tx_deactivate_validator" => TransactionKind::DeactivateValidator(data.to_vec().into()),
             "tx_ibc" => {
                 let decoded_ibc_tx = if let Ok(tx) = namada_ibc::decode_message(data) {
                     tx
@@ -188,32 +411,32 @@ impl TransactionKind {
                     return TransactionKind::Unknown;
                 };
 
-                match decoded_ibc_tx {
-                    namada_ibc::IbcMessage::Envelope(_) => {
-                        TransactionKind::IbcEnvelop(data.to_vec())
-                    }
-                    namada_ibc::IbcMessage::Transfer(_) => {
-                        TransactionKind::IbcTransparentTransfer(data.to_vec())
-                    }
-                    namada_ibc::IbcMessage::ShieldedTransfer(_) => {
-                        TransactionKind::IbcShieldedTransfer(data.to_vec())
-                    }
-                }
+                let kind = match decoded_ibc_tx {
+                    IbcMessage::Envelope(_) => TransactionKind::IbcEnvelop,
+                    IbcMessage::Transfer(_) => TransactionKind::IbcTransparentTransfer,
+                    IbcMessage::ShieldedTransfer(_) => TransactionKind::IbcShieldedTransfer,
+                };
+                kind(TxData::decoded(
+                    data.to_vec(),
+                    DecodedTx::Ibc(decoded_ibc_tx),
+                ))
+            }
+            "tx_change_consensus_key" => TransactionKind::ChangeConsensusKey(data.to_vec().into()),
+            "tx_change_validator_metadata" => TransactionKind::ChangeMetadata(data.to_vec().into()),
+            "tx_change_validator_commission" => {
+                TransactionKind::ChangeCommission(data.to_vec().into())
             }
-            "tx_change_consensus_key" => TransactionKind::ChangeConsensusKey(data.to_vec()),
-            "tx_change_validator_metadata" => TransactionKind::ChangeMetadata(data.to_vec()),
-            "tx_change_validator_commission" => TransactionKind::ChangeCommission(data.to_vec()),
-            "tx_become_validator" => TransactionKind::BecomeValidator(data.to_vec()),
-            "tx_init_account" => TransactionKind::InitAccount(data.to_vec()),
-            "tx_init_proposal" => TransactionKind::InitProposal(data.to_vec()),
-            "tx_resign_steward" => TransactionKind::ResignSteward(data.to_vec()),
-            "tx_reveal_pk" => TransactionKind::RevealPublicKey(data.to_vec()),
-            "tx_unjail_validator" => TransactionKind::UnjailValidator(data.to_vec()),
-            "tx_update_account" => TransactionKind::UpdateAccount(data.to_vec()),
+            "tx_become_validator" => TransactionKind::BecomeValidator(data.to_vec().into()),
+            "tx_init_account" => TransactionKind::InitAccount(data.to_vec().into()),
+            "tx_init_proposal" => TransactionKind::InitProposal(data.to_vec().into()),
+            "tx_resign_steward" => TransactionKind::ResignSteward(data.to_vec().into()),
+            "tx_reveal_pk" => TransactionKind::RevealPublicKey(data.to_vec().into()),
+            "tx_unjail_validator" => TransactionKind::UnjailValidator(data.to_vec().into()),
+            "tx_update_account" => TransactionKind::UpdateAccount(data.to_vec().into()),
             "tx_update_steward_commission" => {
-                TransactionKind::UpdateStewardCommissions(data.to_vec())
+                TransactionKind::UpdateStewardCommissions(data.to_vec().into())
             }
-            "tx_vote_proposal" => TransactionKind::ProposalVote(data.to_vec()),
+            "tx_vote_proposal" => TransactionKind::ProposalVote(data.to_vec().into()),
             _ => TransactionKind::Unknown,
         }
     }
@@ -222,33 +445,135 @@ impl TransactionKind {
         match self {
             TransactionKind::Wrapper => None,
             TransactionKind::Protocol => None,
-            TransactionKind::TransparentTransfer(bytes) => Some(bytes),
-            TransactionKind::ShieldedTransfer(bytes) => Some(bytes),
-            TransactionKind::Bond(bytes) => Some(bytes),
-            TransactionKind::Redelegation(bytes) => Some(bytes),
-            TransactionKind::Unbond(bytes) => Some(bytes),
-            TransactionKind::Withdraw(bytes) => Some(bytes),
-            TransactionKind::ClaimRewards(bytes) => Some(bytes),
-            TransactionKind::ReactivateValidator(bytes) => Some(bytes),
-            TransactionKind::DeactivateValidator(bytes) => Some(bytes),
-            TransactionKind::IbcEnvelop(bytes) => Some(bytes),
-            TransactionKind::IbcTransparentTransfer(bytes) => Some(bytes),
-            TransactionKind::IbcShieldedTransfer(bytes) => Some(bytes),
-            TransactionKind::ChangeConsensusKey(bytes) => Some(bytes),
-            TransactionKind::ChangeCommission(bytes) => Some(bytes),
-            TransactionKind::ChangeMetadata(bytes) => Some(bytes),
-            TransactionKind::BecomeValidator(bytes) => Some(bytes),
-            TransactionKind::InitAccount(bytes) => Some(bytes),
-            TransactionKind::InitProposal(bytes) => Some(bytes),
-            TransactionKind::ResignSteward(bytes) => Some(bytes),
-            TransactionKind::RevealPublicKey(bytes) => Some(bytes),
-            TransactionKind::UnjailValidator(bytes) => Some(bytes),
-            TransactionKind::UpdateAccount(bytes) => Some(bytes),
-            TransactionKind::UpdateStewardCommissions(bytes) => Some(bytes),
-            TransactionKind::ProposalVote(bytes) => Some(bytes),
+            TransactionKind::TransparentTransfer(data) => Some(data.as_bytes()),
+            TransactionKind::ShieldedTransfer(data) => Some(data.as_bytes()),
+            TransactionKind::Bond(data) => Some(data.as_bytes()),
+            TransactionKind::Redelegation(data) => Some(data.as_bytes()),
+            TransactionKind::Unbond(data) => Some(data.as_bytes()),
+            TransactionKind::Withdraw(data) => Some(data.as_bytes()),
+            TransactionKind::ClaimRewards(data) => Some(data.as_bytes()),
+            TransactionKind::ReactivateValidator(data) => Some(data.as_bytes()),
+            TransactionKind::DeactivateValidator(data) => Some(data.as_bytes()),
+            TransactionKind::IbcEnvelop(data) => Some(data.as_bytes()),
+            TransactionKind::IbcTransparentTransfer(data) => Some(data.as_bytes()),
+            TransactionKind::IbcShieldedTransfer(data) => Some(data.as_bytes()),
+            TransactionKind::ChangeConsensusKey(data) => Some(data.as_bytes()),
+            TransactionKind::ChangeCommission(data) => Some(data.as_bytes()),
+            TransactionKind::ChangeMetadata(data) => Some(data.as_bytes()),
+            TransactionKind::BecomeValidator(data) => Some(data.as_bytes()),
+            TransactionKind::InitAccount(data) => Some(data.as_bytes()),
+            TransactionKind::InitProposal(data) => Some(data.as_bytes()),
+            TransactionKind::ResignSteward(data) => Some(data.as_bytes()),
+            TransactionKind::RevealPublicKey(data) => Some(data.as_bytes()),
+            TransactionKind::UnjailValidator(data) => Some(data.as_bytes()),
+            TransactionKind::UpdateAccount(data) => Some(data.as_bytes()),
+            TransactionKind::UpdateStewardCommissions(data) => Some(data.as_bytes()),
+            TransactionKind::ProposalVote(data) => Some(data.as_bytes()),
             TransactionKind::Unknown => None,
         }
     }
+
+    /// Decode the data of this transaction, reusing the result of
+    /// any previous decode.
+    ///
+    /// Decode failures are logged the first time they are hit.
+    pub fn decode(&self) -> Result<&DecodedTx, DecodeError> {
+        type Decoder = fn(&[u8]) -> Result<DecodedTx, String>;
+
+        fn borsh<T: BorshDeserialize>(
+            bytes: &[u8],
+            into_decoded: fn(T) -> DecodedTx,
+        ) -> Result<DecodedTx, String> {
+            T::try_from_slice(bytes)
+                .map(into_decoded)
+                .map_err(|err| err.to_string())
+        }
+
+        let (data, decode): (&TxData, Decoder) = match self {
+            TransactionKind::TransparentTransfer(data)
+            | TransactionKind::ShieldedTransfer(data) => {
+                (data, |bytes| borsh(bytes, DecodedTx::Transfer))
+            }
+            TransactionKind::Bond(data) => (data, |bytes| borsh(bytes, DecodedTx::Bond)),
+            TransactionKind::Redelegation(data) => {
+                (data, |bytes| borsh(bytes, DecodedTx::Redelegation))
+            }
+            TransactionKind::Unbond(data) => (data, |bytes| borsh(bytes, DecodedTx::Unbond)),
+            TransactionKind::Withdraw(data) => (data, |bytes| borsh(bytes, DecodedTx::Withdraw)),
+            TransactionKind::ClaimRewards(data) => {
+                (data, |bytes| borsh(bytes, DecodedTx::ClaimRewards))
+            }
+            TransactionKind::ReactivateValidator(data) => {
+                (data, |bytes| borsh(bytes, DecodedTx::ReactivateValidator))
+            }
+            TransactionKind::DeactivateValidator(data) => {
+                (data, |bytes| borsh(bytes, DecodedTx::DeactivateValidator))
+            }
+            TransactionKind::IbcEnvelop(data)
+            | TransactionKind::IbcTransparentTransfer(data)
+            | TransactionKind::IbcShieldedTransfer(data) => (data, |bytes| {
+                namada_ibc::decode_message(bytes)
+                    .map(DecodedTx::Ibc)
+                    .map_err(|err| err.to_string())
+            }),
+            TransactionKind::ChangeConsensusKey(data) => {
+                (data, |bytes| borsh(bytes, DecodedTx::ChangeConsensusKey))
+            }
+            TransactionKind::ChangeCommission(data) => {
+                (data, |bytes| borsh(bytes, DecodedTx::ChangeCommission))
+            }
+            TransactionKind::ChangeMetadata(data) => {
+                (data, |bytes| borsh(bytes, DecodedTx::ChangeMetadata))
+            }
+            TransactionKind::BecomeValidator(data) => {
+                (data, |bytes| borsh(bytes, DecodedTx::BecomeValidator))
+            }
+            TransactionKind::InitAccount(data) => {
+                (data, |bytes| borsh(bytes, DecodedTx::InitAccount))
+            }
+            TransactionKind::InitProposal(data) => {
+                (data, |bytes| borsh(bytes, DecodedTx::InitProposal))
+            }
+            TransactionKind::ResignSteward(data) => {
+                (data, |bytes| borsh(bytes, DecodedTx::ResignSteward))
+            }
+            TransactionKind::RevealPublicKey(data) => {
+                (data, |bytes| borsh(bytes, DecodedTx::RevealPublicKey))
+            }
+            TransactionKind::UnjailValidator(data) => {
+                (data, |bytes| borsh(bytes, DecodedTx::UnjailValidator))
+            }
+            TransactionKind::UpdateAccount(data) => {
+                (data, |bytes| borsh(bytes, DecodedTx::UpdateAccount))
+            }
+            TransactionKind::UpdateStewardCommissions(data) => (data, |bytes| {
+                borsh(bytes, DecodedTx::UpdateStewardCommissions)
+            }),
+            TransactionKind::ProposalVote(data) => {
+                (data, |bytes| borsh(bytes, DecodedTx::ProposalVote))
+            }
+            TransactionKind::Wrapper | TransactionKind::Protocol | TransactionKind::Unknown => {
+                return Err(DecodeError::NoData {
+                    kind: self.to_string(),
+                })
+            }
+        };
+
+        data.decoded
+            .get_or_init(|| {
+                decode(data.as_bytes()).map(Box::new).map_err(|error| {
+                    let error = DecodeError::InvalidData {
+                        kind: self.to_string(),
+                        error,
+                    };
+                    tracing::warn!(%error, "Failed to decode transaction data");
+                    error
+                })
+            })
+            .as_ref()
+            .map(AsRef::as_ref)
+            .map_err(Clone::clone)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -293,6 +618,28 @@ pub struct Transaction<MEMO = RawMemo> {
 }
 
 impl<M> Transaction<M> {
+    /// Replace the memo of this transaction.
+    pub fn with_memo<N>(self, memo: Option<N>) -> Transaction<N> {
+        let Self {
+            hash,
+            inner_hash,
+            kind,
+            status,
+            memo: _,
+            gas_used,
+            index,
+        } = self;
+        Transaction {
+            hash,
+            inner_hash,
+            kind,
+            status,
+            gas_used,
+            index,
+            memo,
+        }
+    }
+
     pub fn try_parse_memo<N>(self) -> anyhow::Result<Transaction<N>>
     where
         N: TryFrom<M, Error = anyhow::Error>,
@@ -318,9 +665,76 @@ impl<M> Transaction<M> {
     }
 }
 
+/// JSON view of a [`Transaction`], meant for debugging and export.
+#[derive(Debug, Serialize)]
+pub struct TransactionJson {
+    pub hash: String,
+    pub inner_hash: Option<String>,
+    pub index: usize,
+    pub kind: TransactionKindDb,
+    pub status: TransactionExitStatusDb,
+    pub gas_used: u64,
+    pub memo: Option<MemoJson>,
+    /// Fields of the decoded transaction data.
+    pub data: Option<serde_json::Value>,
+    /// Reason why the transaction data could not be decoded.
+    pub data_error: Option<String>,
+}
+
+/// JSON view of the memo of a transaction.
+#[derive(Debug, Serialize)]
+pub struct MemoJson {
+    /// Hex encoded memo.
+    pub hex: String,
+    /// Player credited by the transaction, if the memo holds
+    /// a valid player id.
+    pub player_id: Option<String>,
+    /// Reason why the memo is not a valid player id.
+    pub player_id_error: Option<String>,
+}
+
+impl Transaction<RawMemo> {
+    /// Render this transaction as JSON, along with its decoded data
+    /// and the player id in its memo.
+    pub fn to_json(&self) -> TransactionJson {
+        let memo = self.memo.as_ref().map(|memo| {
+            let hex = String::from_utf8(subtle_encoding::hex::encode(&memo.0)).unwrap();
+            match PlayerId::try_from(memo) {
+                Ok(PlayerId(player_id)) => MemoJson {
+                    hex,
+                    player_id: Some(player_id),
+                    player_id_error: None,
+                },
+                Err(err) => MemoJson {
+                    hex,
+                    player_id: None,
+                    player_id_error: Some(format!("{err:#}")),
+                },
+            }
+        });
+        let (data, data_error) = match self.kind.decode() {
+            Ok(decoded) => (Some(decoded.to_json()), None),
+            Err(DecodeError::NoData { .. }) => (None, None),
+            Err(err) => (None, Some(err.to_string())),
+        };
+
+        TransactionJson {
+            hash: self.hash.to_string(),
+            inner_hash: self.inner_hash.as_ref().map(ToString::to_string),
+            index: self.index,
+            kind: TransactionKindDb::from(&self.kind),
+            status: TransactionExitStatusDb::from(&self.status),
+            gas_used: self.gas_used,
+            memo,
+            data,
+            data_error,
+        }
+    }
+}
+
 impl From<TransactionDb> for Transaction<RawMemo> {
     fn from(db_tx: TransactionDb) -> Self {
-        let data = db_tx.associated_data.unwrap_or_default();
+        let data = TxData::from(db_tx.associated_data.unwrap_or_default());
         let kind = match db_tx.kind {
             TransactionKindDb::Wrapper => TransactionKind::Wrapper,
             TransactionKindDb::Protocol => TransactionKind::Protocol,
@@ -390,7 +804,9 @@ impl Transaction {
                 let tx_id = Id::from(transaction.header_hash());
                 let raw_hash = Id::from(transaction.raw_header_hash());
                 let raw_hash_str = raw_hash.to_string();
-                let tx_status = block_results.find_tx_hash_result(&tx_id).unwrap();
+                let tx_status = block_results
+                    .find_tx_hash_result(&tx_id)
+                    .ok_or_else(|| format!("No valid result event for transaction {tx_id}"))?;
 
                 let tx_exit = TransactionExitStatus::from(&tx_status, &TransactionKind::Wrapper);
                 if tx_exit == TransactionExitStatus::Rejected {
@@ -411,7 +827,9 @@ impl Transaction {
             TxType::Decrypted(_) => {
                 let tx_id = Id::from(transaction.raw_header_hash());
                 let raw_hash = tx_id.to_string();
-                let tx_status = block_results.find_tx_hash_result(&tx_id).unwrap();
+                let tx_status = block_results
+                    .find_tx_hash_result(&tx_id)
+                    .ok_or_else(|| format!("No valid result event for transaction {tx_id}"))?;
 
                 let tx_code_id = transaction
                     .get_section(transaction.code_sechash())
@@ -527,3 +945,56 @@ impl From<&TransactionExitStatus> for TransactionExitStatusDb {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[allow(dead_code)]
+    #[derive(Debug)]
+    enum Envelope {
+        Client(ClientMsg),
+        Packet(PacketMsg),
+    }
+
+    #[allow(dead_code)]
+    #[derive(Debug)]
+    enum ClientMsg {
+        UpdateClient(MsgUpdateClient),
+    }
+
+    #[allow(dead_code)]
+    #[derive(Debug)]
+    struct MsgUpdateClient {
+        client_id: String,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Debug)]
+    enum PacketMsg {
+        Recv(Box<MsgUpdateClient>),
+        Timeout,
+    }
+
+    #[test]
+    fn names_ibc_envelope_messages() {
+        let update_client = || MsgUpdateClient {
+            client_id: "07-tendermint-0".to_owned(),
+        };
+
+        assert_eq!(
+            ibc_message_type(&Envelope::Client(ClientMsg::UpdateClient(update_client()))),
+            "Client::UpdateClient"
+        );
+        assert_eq!(
+            ibc_message_type(&Box::new(Envelope::Packet(PacketMsg::Recv(Box::new(
+                update_client()
+            ))))),
+            "Packet::Recv"
+        );
+        assert_eq!(
+            ibc_message_type(&Envelope::Packet(PacketMsg::Timeout)),
+            "Packet::Timeout"
+        );
+    }
+}